@@ -0,0 +1,136 @@
+use std::cmp::Ordering;
+
+use crate::{Agent, Connection};
+
+/// How a gene lines up between two parents.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GeneKind {
+    /// Both parents have the gene.
+    Matching,
+    /// Only one parent has the gene, and it falls within the other parent's
+    /// range of genes.
+    Disjoint,
+    /// Only one parent has the gene, and it lies beyond the other parent's
+    /// last gene.
+    Excess,
+}
+
+/// A single gene in the alignment of two agents.
+///
+/// The crate doesn't keep innovation numbers, so genes are identified by their
+/// endpoints `(start_layer, start_idx, end_layer, end_idx)` and ordered by them.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GeneAlignment {
+    pub gene: (usize, usize, usize, usize),
+    pub kind: GeneKind,
+    /// The gene's weight in `self` and in `other`, if present.
+    pub weights: (Option<f32>, Option<f32>),
+}
+
+fn genes(agent: &Agent) -> Vec<((usize, usize, usize, usize), f32)> {
+    let mut genes: Vec<_> = agent
+        .connection_list
+        .iter()
+        .map(|connection: &Connection| {
+            (
+                (
+                    connection.start_layer,
+                    connection.start_idx,
+                    connection.end_layer,
+                    connection.end_idx,
+                ),
+                connection.weight,
+            )
+        })
+        .collect();
+
+    genes.sort_by_key(|(gene, _)| *gene);
+    genes.dedup_by_key(|(gene, _)| *gene);
+    genes
+}
+
+impl Agent<'_> {
+    /// Lines up the genes of `self` and `other` the way crossover sees them.
+    pub fn align(&self, other: &Agent) -> Vec<GeneAlignment> {
+        let own = genes(self);
+        let others = genes(other);
+        let own_last = own.last().map(|(gene, _)| *gene);
+        let other_last = others.last().map(|(gene, _)| *gene);
+
+        let mut result = vec![];
+        let (mut i, mut j) = (0, 0);
+
+        while i < own.len() || j < others.len() {
+            let order = match (own.get(i), others.get(j)) {
+                (Some(a), Some(b)) => a.0.cmp(&b.0),
+                (Some(_), None) => Ordering::Less,
+                _ => Ordering::Greater,
+            };
+
+            let (gene, weights, last) = match order {
+                Ordering::Equal => {
+                    let ((gene, a), (_, b)) = (own[i], others[j]);
+                    i += 1;
+                    j += 1;
+                    (gene, (Some(a), Some(b)), None)
+                }
+                Ordering::Less => {
+                    let (gene, a) = own[i];
+                    i += 1;
+                    (gene, (Some(a), None), other_last)
+                }
+                Ordering::Greater => {
+                    let (gene, b) = others[j];
+                    j += 1;
+                    (gene, (None, Some(b)), own_last)
+                }
+            };
+
+            let kind = match (weights, last) {
+                ((Some(_), Some(_)), _) => GeneKind::Matching,
+                (_, Some(last)) if gene < last => GeneKind::Disjoint,
+                _ => GeneKind::Excess,
+            };
+
+            result.push(GeneAlignment {
+                gene,
+                kind,
+                weights,
+            });
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{agent, connection};
+
+    #[test]
+    fn shared_gene_is_matching() {
+        let a = agent(
+            3,
+            0,
+            1,
+            vec![connection(0, 0, 2, 0, 0.5), connection(0, 1, 2, 0, 1.0)],
+        );
+        let b = agent(
+            3,
+            0,
+            1,
+            vec![connection(0, 0, 2, 0, -0.5), connection(0, 2, 2, 0, 2.0)],
+        );
+
+        let alignment = a.align(&b);
+        let kinds: Vec<GeneKind> = alignment.iter().map(|gene| gene.kind).collect();
+
+        assert_eq!(
+            kinds,
+            vec![GeneKind::Matching, GeneKind::Disjoint, GeneKind::Excess]
+        );
+        assert_eq!(alignment[0].weights, (Some(0.5), Some(-0.5)));
+        assert_eq!(alignment[2].weights, (None, Some(2.0)));
+    }
+}
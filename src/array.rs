@@ -0,0 +1,58 @@
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis};
+
+use crate::Agent;
+
+impl Agent<'_> {
+    /// Same as [`Agent::calculate`], but takes and returns `ndarray` arrays.
+    pub fn calculate_array(&mut self, input: ArrayView1<f32>) -> Array1<f32> {
+        let output = match input.as_slice() {
+            Some(input) => self.calculate(input),
+            None => self.calculate(&input.to_vec()),
+        };
+
+        Array1::from(output)
+    }
+
+    /// Runs every row of `inputs` through the network and returns the outputs
+    /// as the rows of a new array.
+    pub fn calculate_batch_array(&mut self, inputs: ArrayView2<f32>) -> Array2<f32> {
+        let mut outputs = Array2::zeros((inputs.nrows(), self.outputs.try_into().unwrap()));
+
+        for (input, mut output) in inputs
+            .axis_iter(Axis(0))
+            .zip(outputs.axis_iter_mut(Axis(0)))
+        {
+            output.assign(&self.calculate_array(input));
+        }
+
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use crate::tests::{agent, connection};
+
+    #[test]
+    fn array_path_matches_vec_path() {
+        let mut agent = agent(
+            2,
+            1,
+            2,
+            vec![
+                connection(0, 0, 1, 0, 0.7),
+                connection(0, 1, 2, 0, -1.2),
+                connection(1, 0, 2, 1, 2.0),
+            ],
+        );
+        let inputs = array![[0.5, -0.3], [1.0, 0.25]];
+
+        let single = agent.calculate_array(inputs.row(0));
+        let batch = agent.calculate_batch_array(inputs.view());
+
+        assert_eq!(single.to_vec(), agent.calculate(&[0.5, -0.3]));
+        assert_eq!(batch.row(1).to_vec(), agent.calculate(&[1.0, 0.25]));
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{Activation, Agent, Connection, NeatError};
+
+const MAGIC: &[u8; 4] = b"SNGN";
+const VERSION: u8 = 3;
+/// Oldest version that can still be read. Version 1 has no weight groups and
+/// versions before 3 have no checksum.
+const MIN_VERSION: u8 = 1;
+const NO_GROUP: u32 = u32::MAX;
+
+/// CRC-32 (IEEE) of `bytes`.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+/// 64 bit FNV-1a hash of `bytes`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/// Reads little-endian values from a byte slice.
+pub(crate) struct Reader<'b> {
+    pub(crate) bytes: &'b [u8],
+}
+
+impl Reader<'_> {
+    pub(crate) fn take(&mut self, len: usize) -> Result<&[u8], NeatError> {
+        if self.bytes.len() < len {
+            return Err(NeatError::UnexpectedEof);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, NeatError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, NeatError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, NeatError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn f32(&mut self) -> Result<f32, NeatError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+impl<'a> Agent<'a> {
+    /// Returns a hash of the genome's binary encoding that doesn't depend on
+    /// the order of the connections.
+    pub fn genome_hash(&self) -> u64 {
+        let mut sorted = self.clone();
+        sorted.sort_connections();
+
+        fnv1a(&sorted.to_bytes())
+    }
+
+    /// Encodes the genome in the versioned little-endian binary format.
+    ///
+    /// The layout is a 4 byte magic header and a version byte, followed by the
+    /// input, hidden node, output and connection counts as `u32`. Every
+    /// connection is then stored as `(u8, u32, u8, u32, f32, u32)` for its
+    /// start layer, start index, end layer, end index, weight and weight group,
+    /// where `u32::MAX` marks a connection without a group. The data ends with
+    /// the number of activation functions, since the functions themselves have
+    /// to be supplied again when decoding, and a CRC-32 of everything before it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+
+        for count in [self.inputs, self.nodes, self.outputs, self.connections] {
+            bytes.extend_from_slice(&(count as u32).to_le_bytes());
+        }
+
+        for connection in &self.connection_list {
+            bytes.push(connection.start_layer as u8);
+            bytes.extend_from_slice(&(connection.start_idx as u32).to_le_bytes());
+            bytes.push(connection.end_layer as u8);
+            bytes.extend_from_slice(&(connection.end_idx as u32).to_le_bytes());
+            bytes.extend_from_slice(&connection.weight.to_le_bytes());
+            bytes.extend_from_slice(
+                &connection
+                    .weight_group
+                    .map_or(NO_GROUP, |group| group as u32)
+                    .to_le_bytes(),
+            );
+        }
+
+        bytes.extend_from_slice(&(self.activation_funcs.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&crc32(&bytes).to_le_bytes());
+
+        bytes
+    }
+
+    /// Decodes a genome written by [`Agent::to_bytes`].
+    pub fn from_bytes(
+        bytes: &[u8],
+        activation_funcs: Vec<&'a Activation>,
+    ) -> Result<Self, NeatError> {
+        let mut reader = Reader { bytes };
+
+        if reader
+            .take(MAGIC.len())
+            .map_err(|_| NeatError::InvalidMagic)?
+            != MAGIC
+        {
+            return Err(NeatError::InvalidMagic);
+        }
+
+        let version = reader.u8()?;
+        if !(MIN_VERSION..=VERSION).contains(&version) {
+            return Err(NeatError::UnsupportedVersion(version));
+        }
+
+        if version >= 3 {
+            let Some(payload_len) = bytes.len().checked_sub(4) else {
+                return Err(NeatError::UnexpectedEof);
+            };
+            let (payload, checksum) = bytes.split_at(payload_len);
+
+            if crc32(payload) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+                return Err(NeatError::ChecksumMismatch);
+            }
+
+            reader.bytes = &payload[MAGIC.len() + 1..];
+        }
+
+        let mut counts = [0; 4];
+        for count in counts.iter_mut() {
+            *count = reader.u32()?;
+            if i32::try_from(*count).is_err() {
+                return Err(NeatError::CountTooLarge);
+            }
+        }
+        let [inputs, nodes, outputs, connections] = counts.map(|count| count as usize);
+        let sizes = [inputs, nodes, outputs];
+
+        // Don't trust the count with an allocation the data can't back.
+        let record_size = if version >= 2 { 18 } else { 14 };
+        if connections > reader.bytes.len() / record_size {
+            return Err(NeatError::UnexpectedEof);
+        }
+        let mut connection_list = Vec::with_capacity(connections);
+
+        for idx in 0..connections {
+            let mut connection = Connection {
+                start_layer: reader.u8()? as usize,
+                start_idx: reader.u32()? as usize,
+                end_layer: reader.u8()? as usize,
+                end_idx: reader.u32()? as usize,
+                weight: reader.f32()?,
+                weight_group: None,
+                activation: None,
+                frozen: false,
+            };
+
+            if version >= 2 {
+                connection.weight_group = match reader.u32()? {
+                    NO_GROUP => None,
+                    group => Some(group as usize),
+                };
+            }
+
+            if !connection.fits(sizes) {
+                return Err(NeatError::InvalidConnection(idx));
+            }
+
+            connection_list.push(connection);
+        }
+
+        let expected = reader.u32()? as usize;
+        if activation_funcs.len() != expected {
+            return Err(NeatError::ActivationCountMismatch {
+                expected,
+                found: activation_funcs.len(),
+            });
+        }
+
+        Ok(Agent {
+            inputs: inputs.try_into().unwrap(),
+            nodes: nodes.try_into().unwrap(),
+            connections: connections.try_into().unwrap(),
+            outputs: outputs.try_into().unwrap(),
+            data_lists: vec![vec![0.0; inputs], vec![0.0; nodes], vec![0.0; outputs]],
+            connection_list,
+            activation_funcs,
+            disabled_inputs: vec![],
+            activation_pool: vec![],
+            activation_clamp: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{agent, connection};
+    use crate::TANH;
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut original = agent(
+            2,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 0.5),
+                connection(0, 1, 2, 0, -1.5),
+                connection(1, 0, 2, 0, 2.0),
+            ],
+        );
+        original.tie_weights(&[0, 2]);
+        let mut decoded = Agent::from_bytes(&original.to_bytes(), vec![TANH, TANH]).unwrap();
+
+        assert_eq!(decoded.connection_list, original.connection_list);
+        assert_eq!(
+            decoded.calculate(&[0.3, -0.7]),
+            original.calculate(&[0.3, -0.7])
+        );
+    }
+
+    #[test]
+    fn corrupted_bytes_are_detected() {
+        let mut bytes = agent(1, 0, 1, vec![connection(0, 0, 2, 0, 1.0)]).to_bytes();
+        let last_weight_byte = bytes.len() - 13;
+        bytes[last_weight_byte] ^= 0x01;
+
+        assert_eq!(
+            Agent::from_bytes(&bytes, vec![TANH, TANH]).err(),
+            Some(NeatError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn crc32_matches_reference() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let mut bytes = agent(1, 0, 1, vec![connection(0, 0, 2, 0, 1.0)]).to_bytes();
+        bytes[MAGIC.len()] = VERSION + 1;
+
+        assert_eq!(
+            Agent::from_bytes(&bytes, vec![TANH, TANH]).err(),
+            Some(NeatError::UnsupportedVersion(VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn genome_hash_ignores_connection_order() {
+        let forward = agent(
+            2,
+            0,
+            1,
+            vec![connection(0, 0, 2, 0, 0.5), connection(0, 1, 2, 0, -0.5)],
+        );
+        let reversed = agent(
+            2,
+            0,
+            1,
+            vec![connection(0, 1, 2, 0, -0.5), connection(0, 0, 2, 0, 0.5)],
+        );
+        let changed = agent(
+            2,
+            0,
+            1,
+            vec![connection(0, 1, 2, 0, -0.5), connection(0, 0, 2, 0, 0.25)],
+        );
+
+        assert_eq!(forward.genome_hash(), reversed.genome_hash());
+        assert_ne!(forward.genome_hash(), changed.genome_hash());
+    }
+
+    #[test]
+    fn oversized_counts_are_rejected() {
+        let header = |counts: [u32; 4]| {
+            let mut bytes = MAGIC.to_vec();
+            bytes.push(1);
+            for count in counts {
+                bytes.extend_from_slice(&count.to_le_bytes());
+            }
+            bytes
+        };
+
+        assert_eq!(
+            Agent::from_bytes(&header([1, 0, 1, u32::MAX]), vec![TANH, TANH]).err(),
+            Some(NeatError::CountTooLarge)
+        );
+        assert_eq!(
+            Agent::from_bytes(&header([1, 0, 1, i32::MAX as u32]), vec![TANH, TANH]).err(),
+            Some(NeatError::UnexpectedEof)
+        );
+        assert_eq!(
+            Agent::from_bytes(&header([u32::MAX, 0, 1, 0]), vec![TANH, TANH]).err(),
+            Some(NeatError::CountTooLarge)
+        );
+    }
+}
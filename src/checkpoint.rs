@@ -0,0 +1,442 @@
+use std::io::{self, Read, Write};
+
+use crate::binary::{crc32, Reader};
+use crate::{Activation, Agent, MutationConfig, NeatError, Population};
+
+const MAGIC: &[u8; 4] = b"SNGP";
+const VERSION: u8 = 3;
+const NONE: u64 = u64::MAX;
+
+/// Returns the index of `func` in `registry`, compared by address.
+fn registry_index(registry: &[&'static Activation], func: &Activation) -> io::Result<u32> {
+    registry
+        .iter()
+        .position(|&known| std::ptr::eq(known, func))
+        .map(|idx| idx as u32)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "activation function missing from the registry",
+            )
+        })
+}
+
+/// Writes the agent in the binary format, followed by the state that format
+/// leaves out: the input mask, the activation clamp, the activation pool and
+/// every connection's frozen flag and activation override.
+fn push_agent(
+    bytes: &mut Vec<u8>,
+    agent: &Agent,
+    registry: &[&'static Activation],
+) -> io::Result<()> {
+    let encoded = agent.to_bytes();
+    bytes.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&encoded);
+
+    bytes.extend_from_slice(&(agent.disabled_inputs.len() as u32).to_le_bytes());
+    bytes.extend(agent.disabled_inputs.iter().map(|&disabled| disabled as u8));
+
+    bytes.push(agent.activation_clamp.is_some() as u8);
+    let (min, max) = agent.activation_clamp.unwrap_or((0.0, 0.0));
+    bytes.extend_from_slice(&min.to_le_bytes());
+    bytes.extend_from_slice(&max.to_le_bytes());
+
+    bytes.extend_from_slice(&(agent.activation_pool.len() as u32).to_le_bytes());
+    for &func in &agent.activation_pool {
+        bytes.extend_from_slice(&registry_index(registry, func)?.to_le_bytes());
+    }
+
+    for connection in &agent.connection_list {
+        bytes.push(connection.frozen as u8);
+        bytes.push(connection.activation.is_some() as u8);
+        if let Some(func) = connection.activation {
+            bytes.extend_from_slice(&registry_index(registry, func)?.to_le_bytes());
+        }
+    }
+
+    Ok(())
+}
+
+fn read_registered(
+    reader: &mut Reader,
+    registry: &[&'static Activation],
+) -> Result<&'static Activation, NeatError> {
+    let idx = reader.u32()? as usize;
+    registry
+        .get(idx)
+        .copied()
+        .ok_or(NeatError::UnknownActivation(idx))
+}
+
+fn read_agent<'a>(
+    reader: &mut Reader,
+    activation_funcs: &[&'a Activation],
+    registry: &[&'static Activation],
+) -> Result<Agent<'a>, NeatError> {
+    let len = reader.u32()? as usize;
+    let mut agent = Agent::from_bytes(reader.take(len)?, activation_funcs.to_vec())?;
+
+    let mask_len = reader.u32()? as usize;
+    agent.disabled_inputs = reader
+        .take(mask_len)?
+        .iter()
+        .map(|&byte| byte != 0)
+        .collect();
+
+    let clamped = reader.u8()? != 0;
+    let clamp = (reader.f32()?, reader.f32()?);
+    agent.activation_clamp = clamped.then_some(clamp);
+
+    let pool_len = reader.u32()? as usize;
+    for _ in 0..pool_len {
+        agent
+            .activation_pool
+            .push(read_registered(reader, registry)?);
+    }
+
+    for connection in agent.connection_list.iter_mut() {
+        connection.frozen = reader.u8()? != 0;
+        connection.activation = match reader.u8()? {
+            0 => None,
+            _ => Some(read_registered(reader, registry)?),
+        };
+    }
+
+    Ok(agent)
+}
+
+fn read_optional(reader: &mut Reader) -> Result<Option<u64>, NeatError> {
+    Ok(match reader.u64()? {
+        NONE => None,
+        value => Some(value),
+    })
+}
+
+impl<'a> Population<'a> {
+    /// Writes a checkpoint of the population to `writer`.
+    ///
+    /// The checkpoint holds every agent in the [binary format](Agent::to_bytes)
+    /// together with its input mask, activation clamp, activation pool and
+    /// frozen connections, the generation counter, the mutation config, the
+    /// fitness history, the best agent so far, the reproduction bias and the
+    /// recorded champions with their limit.
+    ///
+    /// Activation functions of the pool and of single connections are stored
+    /// as their index in `registry`, which has to list every one of them and
+    /// be passed to [`Population::load`] again. The config's `allowed_edges`
+    /// filter is a function pointer and isn't stored, and neither is the RNG
+    /// state, so a loaded population has to be seeded again with
+    /// [`Population::set_seed`] for reproducible runs.
+    pub fn save(&self, mut writer: impl Write, registry: &[&'static Activation]) -> io::Result<()> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(self.generation as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.seed.unwrap_or(NONE).to_le_bytes());
+
+        let config = self.config();
+        for value in [
+            config.new_node_chance,
+            config.new_connection_chance,
+            config.delete_node_chance,
+            config.delete_connection_chance,
+            config.change_weight_chance,
+            config.change_connection_chance,
+            config.max_weight,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for max in [config.max_nodes, config.max_connections] {
+            bytes.extend_from_slice(&max.map_or(NONE, |max| max as u64).to_le_bytes());
+        }
+        bytes.extend_from_slice(&(config.max_connection_attempts as u64).to_le_bytes());
+        bytes.extend_from_slice(&config.perturb_weight_chance.to_le_bytes());
+        bytes.extend_from_slice(&config.perturb_strength.to_le_bytes());
+        bytes.push(config.adaptive_perturbation as u8);
+        for value in [
+            config.flip_sign_chance,
+            config.weight_decay,
+            config.reroute_connection_chance,
+            config.change_activation_chance,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.push(config.force_mutation as u8);
+        bytes.extend_from_slice(&config.add_connected_node_chance.to_le_bytes());
+
+        bytes.push(self.reproduction_bias.is_some() as u8);
+        bytes.extend_from_slice(&self.reproduction_bias.unwrap_or(0.0).to_le_bytes());
+
+        bytes.extend_from_slice(&(self.history.len() as u32).to_le_bytes());
+        for fitness in &self.history {
+            bytes.extend_from_slice(&fitness.to_le_bytes());
+        }
+
+        bytes.push(self.best_ever.is_some() as u8);
+        if let Some((fitness, agent)) = &self.best_ever {
+            bytes.extend_from_slice(&fitness.to_le_bytes());
+            push_agent(&mut bytes, agent, registry)?;
+        }
+
+        bytes.push(self.champion_history.is_some() as u8);
+        bytes.extend_from_slice(
+            &self
+                .champion_limit
+                .map_or(NONE, |limit| limit as u64)
+                .to_le_bytes(),
+        );
+        let champions = self.champion_history();
+        bytes.extend_from_slice(&(champions.len() as u32).to_le_bytes());
+        for agent in champions {
+            push_agent(&mut bytes, agent, registry)?;
+        }
+
+        bytes.extend_from_slice(&(self.agents().len() as u32).to_le_bytes());
+        for agent in self.agents() {
+            push_agent(&mut bytes, agent, registry)?;
+        }
+
+        bytes.extend_from_slice(&crc32(&bytes).to_le_bytes());
+        writer.write_all(&bytes)
+    }
+
+    /// Reads a checkpoint written by [`Population::save`] with the same
+    /// `registry`, giving every agent a copy of `activation_funcs`.
+    pub fn load(
+        mut reader: impl Read,
+        activation_funcs: Vec<&'a Activation>,
+        registry: &[&'static Activation],
+    ) -> Result<Self, NeatError> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|err| NeatError::Io(err.kind()))?;
+
+        if !bytes.starts_with(MAGIC) {
+            return Err(NeatError::InvalidMagic);
+        }
+        let Some(payload_len) = bytes.len().checked_sub(4) else {
+            return Err(NeatError::UnexpectedEof);
+        };
+        let (payload, checksum) = bytes.split_at(payload_len);
+        if payload.len() <= MAGIC.len() {
+            return Err(NeatError::UnexpectedEof);
+        }
+        if payload[MAGIC.len()] != VERSION {
+            return Err(NeatError::UnsupportedVersion(payload[MAGIC.len()]));
+        }
+        if crc32(payload) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+            return Err(NeatError::ChecksumMismatch);
+        }
+
+        let mut reader = Reader {
+            bytes: &payload[MAGIC.len() + 1..],
+        };
+        let generation = reader.u64()? as usize;
+        let seed = read_optional(&mut reader)?;
+
+        // Fields are evaluated in the order they're written, which is the
+        // order `save` stores them in.
+        let config = MutationConfig {
+            new_node_chance: reader.f32()?,
+            new_connection_chance: reader.f32()?,
+            delete_node_chance: reader.f32()?,
+            delete_connection_chance: reader.f32()?,
+            change_weight_chance: reader.f32()?,
+            change_connection_chance: reader.f32()?,
+            max_weight: reader.f32()?,
+            max_nodes: read_optional(&mut reader)?.map(|max| max as usize),
+            max_connections: read_optional(&mut reader)?.map(|max| max as usize),
+            max_connection_attempts: reader.u64()? as usize,
+            perturb_weight_chance: reader.f32()?,
+            perturb_strength: reader.f32()?,
+            adaptive_perturbation: reader.u8()? != 0,
+            flip_sign_chance: reader.f32()?,
+            weight_decay: reader.f32()?,
+            reroute_connection_chance: reader.f32()?,
+            change_activation_chance: reader.f32()?,
+            allowed_edges: None,
+            force_mutation: reader.u8()? != 0,
+            add_connected_node_chance: reader.f32()?,
+        };
+
+        let has_bias = reader.u8()? != 0;
+        let bias = reader.f32()?;
+
+        let history_len = reader.u32()? as usize;
+        let mut history = Vec::with_capacity(history_len);
+        for _ in 0..history_len {
+            history.push(reader.f32()?);
+        }
+
+        let best_ever = match reader.u8()? {
+            0 => None,
+            _ => Some((
+                reader.f32()?,
+                read_agent(&mut reader, &activation_funcs, registry)?,
+            )),
+        };
+
+        let recording = reader.u8()? != 0;
+        let champion_limit = read_optional(&mut reader)?.map(|limit| limit as usize);
+        let champion_count = reader.u32()? as usize;
+        let mut champions = vec![];
+        for _ in 0..champion_count {
+            champions.push(read_agent(&mut reader, &activation_funcs, registry)?);
+        }
+
+        let amount = reader.u32()? as usize;
+        let mut agents = Vec::with_capacity(amount);
+        for _ in 0..amount {
+            agents.push(read_agent(&mut reader, &activation_funcs, registry)?);
+        }
+
+        let mut population = Population::new(agents, config);
+        population.generation = generation;
+        population.seed = seed;
+        population.reproduction_bias = has_bias.then_some(bias);
+        population.history = history;
+        population.best_ever = best_ever;
+        population.champion_history = recording.then_some(champions);
+        population.champion_limit = champion_limit;
+
+        Ok(population)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Connection, ABS, IDENTITY, TANH};
+
+    fn fitness(agent: &mut Agent) -> f32 {
+        agent.calculate(&[0.5, -1.0])[0]
+    }
+
+    #[test]
+    fn checkpoint_round_trip() {
+        let config = MutationConfig {
+            new_connection_chance: 0.6,
+            max_nodes: Some(4),
+            ..MutationConfig::default()
+        };
+        let mut population =
+            Population::new(Agent::create_agents(6, 2, 1, vec![TANH, TANH]), config);
+        population.set_seed(4);
+        for _ in 0..3 {
+            population.evolve(fitness);
+        }
+
+        let mut bytes = vec![];
+        population.save(&mut bytes, &[]).unwrap();
+        let mut loaded = Population::load(&bytes[..], vec![TANH, TANH], &[]).unwrap();
+
+        assert_eq!(loaded.generation(), 3);
+        assert_eq!(loaded.fitness_history(), population.fitness_history());
+        assert_eq!(loaded.config().max_nodes, Some(4));
+        for (a, b) in loaded.agents_mut().iter_mut().zip(population.agents_mut()) {
+            assert_eq!(a.calculate(&[0.5, -1.0]), b.calculate(&[0.5, -1.0]));
+        }
+
+        population.set_seed(11);
+        loaded.set_seed(11);
+        population.evolve(fitness);
+        loaded.evolve(fitness);
+
+        for (a, b) in loaded.agents().iter().zip(population.agents()) {
+            assert_eq!(a.connection_list, b.connection_list);
+        }
+    }
+
+    #[test]
+    fn corrupted_checkpoint_is_detected() {
+        let population = Population::new(
+            Agent::create_agents(2, 1, 1, vec![TANH, TANH]),
+            MutationConfig::default(),
+        );
+        let mut bytes = vec![];
+        population.save(&mut bytes, &[]).unwrap();
+        bytes[6] ^= 0x01;
+
+        assert_eq!(
+            Population::load(&bytes[..], vec![TANH, TANH], &[]).err(),
+            Some(NeatError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn checkpoint_keeps_agent_state() {
+        let mut agents = Agent::create_agents(2, 2, 1, vec![TANH, TANH]);
+        let agent = &mut agents[0];
+        agent.connection_list = vec![
+            Connection::new(0, 0, 2, 0, 0.5),
+            Connection::new(0, 1, 2, 0, -1.5),
+        ];
+        agent.connections = 2;
+        agent.set_connection_frozen(0, true).unwrap();
+        agent.set_connection_activation(1, Some(ABS)).unwrap();
+        agent.activation_pool = vec![IDENTITY, ABS];
+        agent.set_input_mask(&[false, true]).unwrap();
+        agent.set_activation_clamp(Some((-0.5, 0.5)));
+        let population = Population::new(agents, MutationConfig::default());
+
+        let mut bytes = vec![];
+        population.save(&mut bytes, &[ABS, IDENTITY]).unwrap();
+        let loaded = Population::load(&bytes[..], vec![TANH, TANH], &[ABS, IDENTITY]).unwrap();
+        let (original, restored) = (&population.agents()[0], &loaded.agents()[0]);
+
+        assert!(restored.connection_list[0].frozen);
+        assert!(!restored.connection_list[1].frozen);
+        assert!(std::ptr::eq(
+            restored.connection_list[1].activation.unwrap(),
+            ABS
+        ));
+        assert!(restored.connection_list[0].activation.is_none());
+        assert_eq!(restored.activation_pool.len(), 2);
+        assert!(std::ptr::eq(restored.activation_pool[0], IDENTITY));
+        assert_eq!(restored.disabled_inputs, original.disabled_inputs);
+        assert_eq!(restored.activation_clamp, Some((-0.5, 0.5)));
+
+        assert!(population.save(&mut vec![], &[IDENTITY]).is_err());
+        assert_eq!(
+            Population::load(&bytes[..], vec![TANH, TANH], &[ABS]).err(),
+            Some(NeatError::UnknownActivation(1))
+        );
+    }
+
+    #[test]
+    fn checkpoint_keeps_champion_history() {
+        let mut population = Population::new(
+            Agent::create_agents(4, 2, 1, vec![TANH, TANH]),
+            MutationConfig::default(),
+        );
+        population.set_seed(5);
+        population.record_champions(Some(2));
+        for _ in 0..3 {
+            population.evolve(fitness);
+        }
+
+        let mut bytes = vec![];
+        population.save(&mut bytes, &[]).unwrap();
+        let mut loaded = Population::load(&bytes[..], vec![TANH, TANH], &[]).unwrap();
+
+        let hashes = |population: &Population| {
+            population
+                .champion_history()
+                .iter()
+                .map(|agent| agent.genome_hash())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(hashes(&loaded), hashes(&population));
+        assert_eq!(loaded.champion_history().len(), 2);
+
+        loaded.evolve(fitness);
+        assert_eq!(loaded.champion_history().len(), 2);
+
+        let unrecorded = Population::new(vec![], MutationConfig::default());
+        let mut bytes = vec![];
+        unrecorded.save(&mut bytes, &[]).unwrap();
+        let mut loaded = Population::load(&bytes[..], vec![TANH, TANH], &[]).unwrap();
+        loaded.evolve(fitness);
+        assert!(loaded.champion_history.is_none());
+    }
+}
@@ -0,0 +1,110 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{Agent, Population};
+
+/// Two populations evolved against each other, where fitness comes from
+/// matches between their agents.
+#[derive(Clone)]
+pub struct CoEvolution<'a> {
+    first: Population<'a>,
+    second: Population<'a>,
+    /// Matches every agent starts per generation.
+    opponents: usize,
+    rng: StdRng,
+}
+
+impl<'a> CoEvolution<'a> {
+    pub fn new(first: Population<'a>, second: Population<'a>, opponents: usize) -> Self {
+        CoEvolution {
+            first,
+            second,
+            opponents,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Reseeds the RNG used to sample opponents.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn first(&self) -> &Population<'a> {
+        &self.first
+    }
+
+    pub fn second(&self) -> &Population<'a> {
+        &self.second
+    }
+
+    /// Runs one generation.
+    ///
+    /// Every agent of either population plays `opponents` matches against
+    /// randomly sampled agents of the other one. `play` gets the agent from
+    /// the first population first and returns both players' rewards, which
+    /// are summed into their fitness. Both populations then evolve on those
+    /// fitnesses, which are returned.
+    pub fn step<F: Fn(&mut Agent<'a>, &mut Agent<'a>) -> (f32, f32)>(
+        &mut self,
+        play: F,
+    ) -> (Vec<f32>, Vec<f32>) {
+        let first = self.first.agents_mut();
+        let second = self.second.agents_mut();
+        let mut first_fitness = vec![0.0; first.len()];
+        let mut second_fitness = vec![0.0; second.len()];
+
+        if !first.is_empty() && !second.is_empty() {
+            for idx in 0..first.len() {
+                for _ in 0..self.opponents {
+                    let opponent = self.rng.gen_range(0..second.len());
+                    let (a, b) = play(&mut first[idx], &mut second[opponent]);
+                    first_fitness[idx] += a;
+                    second_fitness[opponent] += b;
+                }
+            }
+
+            for idx in 0..second.len() {
+                for _ in 0..self.opponents {
+                    let opponent = self.rng.gen_range(0..first.len());
+                    let (a, b) = play(&mut first[opponent], &mut second[idx]);
+                    first_fitness[opponent] += a;
+                    second_fitness[idx] += b;
+                }
+            }
+        }
+
+        let mut scores = first_fitness.iter();
+        self.first.evolve(|_| *scores.next().unwrap());
+        let mut scores = second_fitness.iter();
+        self.second.evolve(|_| *scores.next().unwrap());
+
+        (first_fitness, second_fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MutationConfig, TANH};
+
+    #[test]
+    fn symmetric_game_rewards_both_sides() {
+        let population = |size| {
+            Population::new(
+                Agent::create_agents(size, 1, 1, vec![TANH, TANH]),
+                MutationConfig::default(),
+            )
+        };
+        let mut coevolution = CoEvolution::new(population(4), population(3), 2);
+        coevolution.set_seed(1);
+
+        let (first, second) = coevolution.step(|_, _| (1.0, 1.0));
+
+        assert_eq!(first.len(), 4);
+        assert_eq!(second.len(), 3);
+        assert!(first.iter().chain(&second).all(|&fitness| fitness >= 2.0));
+        assert_eq!(first.iter().sum::<f32>(), 14.0);
+        assert_eq!(second.iter().sum::<f32>(), 14.0);
+        assert_eq!(coevolution.first().generation(), 1);
+        assert_eq!(coevolution.second().generation(), 1);
+    }
+}
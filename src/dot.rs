@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+use crate::{Agent, Population};
+
+/// Prefix of the DOT node names of each layer.
+const PREFIXES: [&str; 3] = ["i", "h", "o"];
+
+impl Agent<'_> {
+    /// Renders the network as a Graphviz DOT graph, with inputs on the left
+    /// and outputs on the right. Edges are labelled with their weights.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph agent {\n    rankdir=LR;\n");
+        let sizes = [self.inputs, self.nodes, self.outputs];
+
+        for (layer, size) in sizes.iter().enumerate() {
+            let rank = if layer == 1 { "" } else { "rank=same; " };
+            let nodes: Vec<String> = (0..*size)
+                .map(|idx| format!("{}{}", PREFIXES[layer], idx))
+                .collect();
+
+            if !nodes.is_empty() {
+                writeln!(dot, "    {{ {}{}; }}", rank, nodes.join("; ")).unwrap();
+            }
+        }
+
+        for connection in &self.connection_list {
+            writeln!(
+                dot,
+                "    {}{} -> {}{} [label=\"{:.2}\"];",
+                PREFIXES[connection.start_layer],
+                connection.start_idx,
+                PREFIXES[connection.end_layer],
+                connection.end_idx,
+                connection.weight
+            )
+            .unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<'a> Population<'a> {
+    /// Same as [`Population::evolve`], but every `every` generations passes the
+    /// generation number and the champion's [DOT graph](Agent::to_dot) to
+    /// `log`, e.g. to render frames of the topology over time.
+    pub fn evolve_logging_dot(
+        &mut self,
+        fitness: impl FnMut(&mut Agent<'a>) -> f32,
+        every: usize,
+        mut log: impl FnMut(usize, &str),
+    ) -> f32 {
+        let best = self.evolve(fitness);
+
+        if every > 0 && self.generation().is_multiple_of(every) {
+            if let Some(champion) = self.agents().first() {
+                log(self.generation(), &champion.to_dot());
+            }
+        }
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::{agent, connection},
+        MutationConfig, TANH,
+    };
+
+    #[test]
+    fn dot_lists_nodes_and_edges() {
+        let agent = agent(
+            1,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 0.5), connection(1, 0, 2, 0, -1.25)],
+        );
+
+        let dot = agent.to_dot();
+
+        assert!(dot.starts_with("digraph agent {"));
+        assert!(dot.contains("{ h0; }"));
+        assert!(dot.contains("i0 -> h0 [label=\"0.50\"];"));
+        assert!(dot.contains("h0 -> o0 [label=\"-1.25\"];"));
+    }
+
+    #[test]
+    fn champion_dot_is_logged_every_generation() {
+        let agents = Agent::create_agents(4, 2, 1, vec![TANH, TANH]);
+        let mut population = Population::new(agents, MutationConfig::default());
+        let mut frames = vec![];
+
+        for _ in 0..3 {
+            population.evolve_logging_dot(
+                |agent| agent.calculate(&[1.0, 0.5])[0],
+                1,
+                |generation, dot| frames.push((generation, dot.to_string())),
+            );
+        }
+
+        assert_eq!(
+            frames
+                .iter()
+                .map(|(generation, _)| *generation)
+                .collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert!(frames.iter().all(|(_, dot)| !dot.is_empty()));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{Agent, NeatError};
+
+/// Agents with the same inputs and outputs that predict together by
+/// averaging their outputs.
+#[derive(Clone)]
+pub struct Ensemble<'a> {
+    members: Vec<Agent<'a>>,
+}
+
+impl<'a> Ensemble<'a> {
+    /// Fails if any member's input or output count differs from the first
+    /// member's.
+    pub fn new(members: Vec<Agent<'a>>) -> Result<Self, NeatError> {
+        if let Some(first) = members.first() {
+            let shape = (first.inputs, first.outputs);
+
+            if let Some(idx) = members
+                .iter()
+                .position(|member| (member.inputs, member.outputs) != shape)
+            {
+                return Err(NeatError::ShapeMismatch(idx));
+            }
+        }
+
+        Ok(Ensemble { members })
+    }
+
+    pub fn members(&self) -> &[Agent<'a>] {
+        &self.members
+    }
+
+    /// Returns the mean of every member's outputs for `input`, or an empty
+    /// list for an empty ensemble.
+    pub fn predict(&mut self, input: &[f32]) -> Vec<f32> {
+        let mut sum: Vec<f32> = vec![];
+
+        for member in self.members.iter_mut() {
+            let output = member.calculate(input);
+            sum.resize(output.len(), 0.0);
+
+            for (total, value) in sum.iter_mut().zip(output) {
+                *total += value;
+            }
+        }
+
+        let count = self.members.len() as f32;
+        sum.iter().map(|total| total / count).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{agent, connection};
+
+    #[test]
+    fn predicts_mean_of_members() {
+        let mut first = agent(1, 0, 2, vec![connection(0, 0, 2, 0, 1.0)]);
+        let mut second = agent(1, 0, 2, vec![connection(0, 0, 2, 1, -2.0)]);
+        let expected: Vec<f32> = first
+            .calculate(&[0.4])
+            .iter()
+            .zip(second.calculate(&[0.4]))
+            .map(|(a, b)| (a + b) / 2.0)
+            .collect();
+
+        let mut ensemble = Ensemble::new(vec![first, second]).unwrap();
+
+        assert_eq!(ensemble.predict(&[0.4]), expected);
+    }
+
+    #[test]
+    fn rejects_mismatched_shapes() {
+        let members = vec![agent(1, 0, 2, vec![]), agent(2, 0, 2, vec![])];
+
+        assert_eq!(
+            Ensemble::new(members).err(),
+            Some(NeatError::ShapeMismatch(1))
+        );
+    }
+}
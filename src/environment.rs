@@ -0,0 +1,99 @@
+use crate::{Agent, Population};
+
+/// A simulation agents interact with step by step, RL style.
+///
+/// Observations are fed to the agent as inputs and its outputs are passed
+/// back as the action, so their sizes have to match the agent's.
+pub trait Environment {
+    /// Starts a new episode and returns the first observation.
+    fn reset(&mut self) -> Vec<f32>;
+    /// Applies `action` and returns the next observation, the reward and
+    /// whether the episode is over.
+    fn step(&mut self, action: &[f32]) -> (Vec<f32>, f32, bool);
+}
+
+impl Agent<'_> {
+    /// Runs one episode of `env`, stopping after `max_steps` steps at the
+    /// latest, and returns the summed reward.
+    pub fn run_episode(&mut self, env: &mut impl Environment, max_steps: usize) -> f32 {
+        let mut observation = env.reset();
+        let mut total = 0.0;
+
+        for _ in 0..max_steps {
+            let action = self.calculate(&observation);
+            let (next, reward, done) = env.step(&action);
+            total += reward;
+
+            if done {
+                break;
+            }
+            observation = next;
+        }
+
+        total
+    }
+}
+
+impl Population<'_> {
+    /// Runs every agent through `episodes` episodes of `env` and returns each
+    /// agent's summed reward, for use as its fitness.
+    pub fn evaluate_in_env(
+        &mut self,
+        env: &mut impl Environment,
+        episodes: usize,
+        max_steps: usize,
+    ) -> Vec<f32> {
+        self.agents_mut()
+            .iter_mut()
+            .map(|agent| {
+                (0..episodes)
+                    .map(|_| agent.run_episode(env, max_steps))
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::{agent, connection},
+        MutationConfig,
+    };
+
+    /// Rewards a positive action for ten steps.
+    struct PushRight {
+        steps: usize,
+    }
+
+    impl Environment for PushRight {
+        fn reset(&mut self) -> Vec<f32> {
+            self.steps = 0;
+            vec![1.0]
+        }
+
+        fn step(&mut self, action: &[f32]) -> (Vec<f32>, f32, bool) {
+            self.steps += 1;
+            let reward = if action[0] > 0.0 { 1.0 } else { 0.0 };
+
+            (vec![1.0], reward, self.steps == 10)
+        }
+    }
+
+    #[test]
+    fn constant_action_collects_reward() {
+        let agents = vec![
+            agent(1, 0, 1, vec![connection(0, 0, 2, 0, 1.0)]),
+            agent(1, 0, 1, vec![connection(0, 0, 2, 0, -1.0)]),
+        ];
+        let mut population = Population::new(agents, MutationConfig::default());
+        let mut env = PushRight { steps: 0 };
+
+        assert_eq!(
+            population.evaluate_in_env(&mut env, 2, 100),
+            vec![20.0, 0.0]
+        );
+        assert_eq!(population.agents_mut()[0].run_episode(&mut env, 4), 4.0);
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NeatError {
+    /// The data doesn't start with the genome magic header.
+    InvalidMagic,
+    /// The genome was written with a format version this crate can't read.
+    UnsupportedVersion(u8),
+    /// The data ended before the genome was fully read.
+    UnexpectedEof,
+    /// The number of activation functions doesn't match the genome.
+    ActivationCountMismatch { expected: usize, found: usize },
+    /// The connection at this index refers to a node that doesn't exist.
+    InvalidConnection(usize),
+    /// The evaluation order isn't a permutation of the hidden node indices.
+    InvalidOrder,
+    /// There is no activation function for this source layer.
+    MissingActivation(usize),
+    /// The value list of this layer doesn't match its node count.
+    LayerSizeMismatch(usize),
+    /// The connection counter doesn't match the number of connections.
+    ConnectionCountMismatch,
+    /// There is no connection at this index.
+    NoSuchConnection(usize),
+    /// The genome data doesn't match its checksum.
+    ChecksumMismatch,
+    /// A per-input value doesn't match the number of inputs.
+    InputSizeMismatch { expected: usize, found: usize },
+    /// This agent's input or output count differs from the other agents.
+    ShapeMismatch(usize),
+    /// The hidden nodes form a cycle, so they have no feed-forward order.
+    Cycle,
+    /// This line of an edge list (counted from 1) can't be parsed.
+    MalformedLine(usize),
+    /// A node or connection count doesn't fit in an `i32`.
+    CountTooLarge,
+    /// There is no activation function at this index of the registry.
+    UnknownActivation(usize),
+    /// Reading or writing the data failed.
+    Io(std::io::ErrorKind),
+}
+
+impl fmt::Display for NeatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NeatError::InvalidMagic => write!(f, "Data is not a simple_neat genome"),
+            NeatError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported genome format version ({})", version)
+            }
+            NeatError::UnexpectedEof => write!(f, "Unexpected end of genome data"),
+            NeatError::ActivationCountMismatch { expected, found } => write!(
+                f,
+                "Activation function count ({}) doesn't match the genome ({})",
+                found, expected
+            ),
+            NeatError::InvalidConnection(idx) => {
+                write!(f, "Connection {} refers to a node that doesn't exist", idx)
+            }
+            NeatError::InvalidOrder => write!(
+                f,
+                "Evaluation order isn't a permutation of the hidden node indices"
+            ),
+            NeatError::MissingActivation(layer) => {
+                write!(f, "No activation function for layer {}", layer)
+            }
+            NeatError::LayerSizeMismatch(layer) => {
+                write!(f, "Values of layer {} don't match its node count", layer)
+            }
+            NeatError::ConnectionCountMismatch => write!(
+                f,
+                "Connection count doesn't match the number of connections"
+            ),
+            NeatError::NoSuchConnection(idx) => write!(f, "There is no connection {}", idx),
+            NeatError::ChecksumMismatch => write!(f, "Genome data doesn't match its checksum"),
+            NeatError::InputSizeMismatch { expected, found } => write!(
+                f,
+                "Input size ({}) doesn't match target input size ({})",
+                found, expected
+            ),
+            NeatError::ShapeMismatch(idx) => write!(
+                f,
+                "Agent {} has a different input or output count than the others",
+                idx
+            ),
+            NeatError::Cycle => write!(f, "Hidden nodes form a cycle"),
+            NeatError::MalformedLine(line) => {
+                write!(f, "Line {} of the edge list is malformed", line)
+            }
+            NeatError::CountTooLarge => write!(f, "Node or connection count is too large"),
+            NeatError::UnknownActivation(idx) => {
+                write!(f, "No activation function at registry index {}", idx)
+            }
+            NeatError::Io(kind) => write!(f, "I/O error ({})", kind),
+        }
+    }
+}
+
+impl std::error::Error for NeatError {}
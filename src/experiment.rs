@@ -0,0 +1,121 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{Activation, Agent, MutationConfig, Population};
+
+/// Everything needed to create a fresh population.
+#[derive(Clone)]
+pub struct PopulationConfig {
+    pub size: i32,
+    pub inputs: i32,
+    pub outputs: i32,
+    pub activation_funcs: Vec<&'static Activation>,
+    pub mutation: MutationConfig,
+}
+
+impl Population<'static> {
+    /// Creates a population of unconnected agents, seeding its RNG with `seed`.
+    pub fn from_config(config: &PopulationConfig, seed: u64) -> Self {
+        let agents = Agent::create_agents(
+            config.size,
+            config.inputs,
+            config.outputs,
+            config.activation_funcs.clone(),
+        );
+        let mut population = Population::new(agents, config.mutation);
+        population.set_seed(seed);
+
+        population
+    }
+}
+
+/// The outcome of one seed of an [`Experiment`].
+#[derive(Clone)]
+pub struct RunResult {
+    pub seed: u64,
+    pub best_fitness: f32,
+    /// The first agent of the final population, or `None` if it's empty.
+    pub champion: Option<Agent<'static>>,
+}
+
+/// Runs the same evolution setup once per seed.
+#[derive(Clone)]
+pub struct Experiment {
+    pub config: PopulationConfig,
+    pub seeds: Vec<u64>,
+    pub generations: usize,
+}
+
+impl Experiment {
+    /// Evolves a population for every seed and returns the results in seed
+    /// order. With the `rayon` feature the seeds run in parallel.
+    pub fn run(&self, fitness: impl Fn(&mut Agent<'static>) -> f32 + Sync) -> Vec<RunResult> {
+        let run_seed = |&seed: &u64| {
+            let mut population = Population::from_config(&self.config, seed);
+            let mut best_fitness = f32::NEG_INFINITY;
+
+            for _ in 0..self.generations {
+                best_fitness = population.evolve(&fitness);
+            }
+
+            RunResult {
+                seed,
+                best_fitness,
+                champion: population.agents().first().cloned(),
+            }
+        };
+
+        #[cfg(feature = "rayon")]
+        return self.seeds.par_iter().map(run_seed).collect();
+
+        #[cfg(not(feature = "rayon"))]
+        return self.seeds.iter().map(run_seed).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TANH;
+
+    #[test]
+    fn one_result_per_seed() {
+        let experiment = Experiment {
+            config: PopulationConfig {
+                size: 4,
+                inputs: 1,
+                outputs: 1,
+                activation_funcs: vec![TANH, TANH],
+                mutation: MutationConfig::default(),
+            },
+            seeds: vec![1, 2],
+            generations: 5,
+        };
+
+        let results = experiment.run(|agent| agent.calculate(&[1.0])[0]);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].seed, 1);
+        assert_eq!(results[1].seed, 2);
+        assert!(results.iter().all(|result| result.best_fitness.is_finite()));
+    }
+
+    #[test]
+    fn empty_population_has_no_champion() {
+        let experiment = Experiment {
+            config: PopulationConfig {
+                size: 0,
+                inputs: 1,
+                outputs: 1,
+                activation_funcs: vec![TANH, TANH],
+                mutation: MutationConfig::default(),
+            },
+            seeds: vec![3],
+            generations: 2,
+        };
+
+        let results = experiment.run(|agent| agent.calculate(&[1.0])[0]);
+
+        assert!(results[0].champion.is_none());
+    }
+}
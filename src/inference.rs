@@ -0,0 +1,83 @@
+use crate::Agent;
+
+impl<'a> Agent<'a> {
+    /// Turns the agent into a closure that runs the same forward pass as
+    /// [`Agent::calculate`].
+    ///
+    /// The connections are sorted into evaluation order and every endpoint is
+    /// resolved to an index into one flat value buffer up front, so a call
+    /// only walks a list of edges. Panics if the agent doesn't pass
+    /// [`Agent::validate`].
+    pub fn into_fn(mut self) -> impl FnMut(&[f32]) -> Vec<f32> + 'a {
+        if let Err(err) = self.validate() {
+            panic!("Can't compile an invalid agent: {}", err);
+        }
+        self.sort_connections();
+
+        let inputs = self.data_lists[0].len();
+        let offsets = [0, inputs, inputs + self.data_lists[1].len()];
+        let mut values = vec![0.0; offsets[2] + self.data_lists[2].len()];
+        let edges: Vec<(usize, usize)> = self
+            .connection_list
+            .iter()
+            .map(|connection| {
+                (
+                    offsets[connection.start_layer] + connection.start_idx,
+                    offsets[connection.end_layer] + connection.end_idx,
+                )
+            })
+            .collect();
+
+        move |input| {
+            if input.len() != inputs {
+                panic!(
+                    "Input size ({}) doesn't match target input size ({})",
+                    input.len(),
+                    inputs
+                );
+            }
+
+            values.fill(0.0);
+            for (value, input) in values.iter_mut().zip(self.masked_input(input)) {
+                *value = input;
+            }
+
+            for (connection, &(start, end)) in self.connection_list.iter().zip(&edges) {
+                values[end] += connection.transmit(
+                    &self.activation_funcs,
+                    self.activation_clamp,
+                    values[start],
+                );
+            }
+
+            values[offsets[2]..].to_vec()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{agent, connection};
+
+    #[test]
+    fn closure_matches_calculate() {
+        let mut original = agent(
+            2,
+            2,
+            2,
+            vec![
+                connection(1, 1, 2, 0, 0.5),
+                connection(0, 0, 1, 0, 1.5),
+                connection(1, 0, 1, 1, -0.7),
+                connection(0, 1, 2, 1, 0.9),
+                connection(1, 0, 2, 1, 2.0),
+            ],
+        );
+        original.set_input_mask(&[false, true]).unwrap();
+        let mut compiled = original.clone().into_fn();
+
+        for input in [[0.3, -0.7], [1.0, 1.0], [-2.0, 0.5], [0.0, 0.0]] {
+            assert_eq!(compiled(&input), original.calculate(&input));
+        }
+    }
+}
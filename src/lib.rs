@@ -1,58 +1,522 @@
-use std::{cmp::Ordering, f32::consts::E};
+use std::{cmp::Ordering, fmt};
 
-use rand::{thread_rng, Rng};
+use num_traits::float::TotalOrder;
+use rand::{seq::SliceRandom, thread_rng, Rng, RngCore};
 
-pub const TANH: &dyn Fn(f32) -> f32 = &|x| (E.powf(x) - E.powf(-x)) / (E.powf(x) + E.powf(-x));
+mod alignment;
+#[cfg(feature = "ndarray")]
+mod array;
+mod binary;
+mod checkpoint;
+mod coevolution;
+mod dot;
+mod ensemble;
+mod environment;
+mod error;
+mod experiment;
+mod inference;
+mod manifest;
+mod multi_objective;
+mod mutation;
+mod parts;
+mod population;
+mod replay;
+mod schedule;
+mod scratch;
+mod selection;
+mod tune;
+mod typed;
 
+pub use alignment::{GeneAlignment, GeneKind};
+pub use coevolution::CoEvolution;
+pub use ensemble::Ensemble;
+pub use environment::Environment;
+pub use error::NeatError;
+pub use experiment::{Experiment, PopulationConfig, RunResult};
+pub use manifest::Manifest;
+pub use multi_objective::MultiObjectivePopulation;
+pub use mutation::{
+    AddConnectedNode, AddConnection, AddNode, ChangeActivation, ChangeConnection, ChangeWeight,
+    DeleteConnection, DeleteNode, FlipSign, Mutation, PerturbWeight, Pipeline, RerouteConnection,
+};
+pub use num_traits::Float;
+pub use parts::AgentMeta;
+pub use population::Population;
+pub use replay::ReplayContext;
+pub use schedule::{LinearAnneal, MutationSchedule};
+pub use scratch::Scratch;
+pub use selection::{RankBias, Roulette, Selection, Tournament, Truncation};
+pub use tune::{tune, Parameter};
+pub use typed::{FromOutputs, IntoInputs};
+
+/// An activation function applied to a node's value before it's passed on.
+pub type Activation<F = f32> = dyn Fn(F) -> F + Sync;
+
+pub const TANH: &Activation = &|x| x.tanh();
+pub const ABS: &Activation = &|x| x.abs();
+pub const IDENTITY: &Activation = &|x| x;
+/// Rational approximation of [`TANH`], within 1e-4 of it everywhere.
+pub const TANH_FAST: &Activation = &fast_tanh;
+/// Logistic sigmoid built on [`TANH_FAST`], within 1e-4 of `1 / (1 + e^-x)`.
+pub const SIGMOID_FAST: &Activation = &|x| 0.5 + 0.5 * fast_tanh(0.5 * x);
+
+/// Lambert's continued fraction for tanh cut off after seven terms, clamped
+/// to the range of tanh. Saturates beyond 5, where tanh is within 1e-4 of 1.
+fn fast_tanh(x: f32) -> f32 {
+    if x.abs() >= 5.0 {
+        return x.signum();
+    }
+
+    let x2 = x * x;
+    let numerator = x * (135135.0 + x2 * (17325.0 + x2 * (378.0 + x2)));
+    let denominator = 135135.0 + x2 * (62370.0 + x2 * (3150.0 + x2 * 28.0));
+
+    (numerator / denominator).clamp(-1.0, 1.0)
+}
+
+/// [`TANH`] for any float type, e.g. `&tanh::<f64>` for an `Agent<f64>`.
+pub fn tanh<F: Float>(x: F) -> F {
+    x.tanh()
+}
+
+/// [`ABS`] for any float type.
+pub fn abs<F: Float>(x: F) -> F {
+    x.abs()
+}
+
+/// [`IDENTITY`] for any float type.
+pub fn identity<F: Float>(x: F) -> F {
+    x
+}
+
+/// A network of input, hidden and output nodes.
+///
+/// Node values and weights are `f32` by default. Building, validating and
+/// running an agent works for any [`Float`], e.g. `Agent<f64>` for more
+/// precise sums; mutation, reproduction and serialization are `f32` only.
 #[derive(Clone)]
-pub struct Agent<'a> {
+pub struct Agent<'a, F: 'static = f32> {
     inputs: i32,
     nodes: i32,
     connections: i32,
     outputs: i32,
-    data_lists: Vec<Vec<f32>>,
-    connection_list: Vec<Connection>,
-    activation_funcs: Vec<&'a dyn Fn(f32) -> f32>,
+    data_lists: Vec<Vec<F>>,
+    connection_list: Vec<Connection<F>>,
+    activation_funcs: Vec<&'a Activation<F>>,
+    /// Inputs marked `true` are read as 0. Empty when no mask is set.
+    disabled_inputs: Vec<bool>,
+    /// Activation functions that mutations may assign to single connections.
+    activation_pool: Vec<&'static Activation<F>>,
+    /// Bounds every node value is clamped to before it's passed on.
+    activation_clamp: Option<(F, F)>,
 }
 
+/// Decides from `(start_layer, start_idx, end_layer, end_idx)` whether a
+/// connection may exist.
+pub type EdgeFilter = fn(usize, usize, usize, usize) -> bool;
+
+/// How often a forced mutation is retried before a weight is redrawn directly.
+const FORCED_MUTATION_ATTEMPTS: usize = 100;
+
+/// `(start_layer, start_idx, end_layer, end_idx)` of a connection.
+type Endpoints = (usize, usize, usize, usize);
+
 #[derive(Clone, Copy)]
-pub struct Connection {
+pub struct Connection<F: 'static = f32> {
     start_layer: usize,
     end_layer: usize,
     start_idx: usize,
     end_idx: usize,
-    weight: f32,
+    weight: F,
+    /// Connections sharing a group always carry the same weight.
+    weight_group: Option<usize>,
+    /// Replaces the start layer's activation function for this connection.
+    activation: Option<&'static Activation<F>>,
+    /// Frozen connections are left alone by every mutation.
+    frozen: bool,
 }
 
-impl Agent<'_> {
+impl<F: Float + 'static> PartialEq for Connection<F> {
+    /// Activation overrides are compared by address.
+    fn eq(&self, other: &Self) -> bool {
+        self.same_endpoints(other)
+            && self.weight == other.weight
+            && self.weight_group == other.weight_group
+            && self.frozen == other.frozen
+            && match (self.activation, other.activation) {
+                (Some(a), Some(b)) => std::ptr::eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
+impl<F: fmt::Debug + 'static> fmt::Debug for Connection<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connection")
+            .field("start_layer", &self.start_layer)
+            .field("end_layer", &self.end_layer)
+            .field("start_idx", &self.start_idx)
+            .field("end_idx", &self.end_idx)
+            .field("weight", &self.weight)
+            .field("weight_group", &self.weight_group)
+            .field("activation", &self.activation.map(|_| "custom"))
+            .field("frozen", &self.frozen)
+            .finish()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct MutationConfig {
+    pub new_node_chance: f32,
+    pub new_connection_chance: f32,
+    pub delete_node_chance: f32,
+    pub delete_connection_chance: f32,
+    pub change_weight_chance: f32,
+    pub change_connection_chance: f32,
+    pub max_weight: f32,
+    /// Hidden node count at which node-adding mutations are skipped.
+    pub max_nodes: Option<usize>,
+    /// Connection count at which connection-adding mutations are skipped.
+    pub max_connections: Option<usize>,
+    /// How many random endpoints are tried before an add-connection mutation
+    /// gives up on finding a connection that doesn't exist yet.
+    pub max_connection_attempts: usize,
+    /// Chance to nudge a random weight by up to `perturb_strength`, instead of
+    /// redrawing it completely like `change_weight_chance` does.
+    pub perturb_weight_chance: f32,
+    pub perturb_strength: f32,
+    /// Scales `perturb_strength` by the agent's mean absolute weight.
+    pub adaptive_perturbation: bool,
+    /// Chance to negate the weight of a random connection.
+    pub flip_sign_chance: f32,
+    /// Every weight is multiplied by `1 - weight_decay` on reproduction.
+    pub weight_decay: f32,
+    /// Chance to move one endpoint of a random connection with
+    /// [`Agent::reroute_connection`].
+    pub reroute_connection_chance: f32,
+    /// Chance to give a random connection an activation function from the
+    /// agent's [activation pool](Agent::activation_pool).
+    pub change_activation_chance: f32,
+    /// Structural mutations never create connections this filter rejects.
+    pub allowed_edges: Option<EdgeFilter>,
+    /// Retries reproduction until the offspring differs from its parent.
+    pub force_mutation: bool,
+    /// Chance to add a hidden node wired from a random input and to a random
+    /// output, unlike the disconnected node of `new_node_chance`.
+    pub add_connected_node_chance: f32,
+}
+
+/// How new connection weights are drawn.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WeightInit {
+    /// Uniformly between `-max` and `max`.
+    Uniform(f32),
+    /// Always the given weight.
+    Constant(f32),
+}
+
+impl WeightInit {
+    pub fn sample(&self, rng: &mut impl Rng) -> f32 {
+        match *self {
+            WeightInit::Uniform(max) => rng.gen_range(-max..max),
+            WeightInit::Constant(weight) => weight,
+        }
+    }
+}
+
+impl Default for MutationConfig {
+    fn default() -> Self {
+        MutationConfig {
+            new_node_chance: 0.1,
+            new_connection_chance: 0.15,
+            delete_node_chance: 0.05,
+            delete_connection_chance: 0.05,
+            change_weight_chance: 0.20,
+            change_connection_chance: 0.15,
+            max_weight: 3.0,
+            max_nodes: None,
+            max_connections: None,
+            max_connection_attempts: 20,
+            perturb_weight_chance: 0.0,
+            perturb_strength: 0.1,
+            adaptive_perturbation: false,
+            flip_sign_chance: 0.0,
+            weight_decay: 0.0,
+            reroute_connection_chance: 0.0,
+            change_activation_chance: 0.0,
+            allowed_edges: None,
+            force_mutation: false,
+            add_connected_node_chance: 0.0,
+        }
+    }
+}
+
+impl<F: Float + 'static> Connection<F> {
+    /// Creates a connection from node `start_idx` of `start_layer` to node
+    /// `end_idx` of `end_layer`.
+    pub fn new(
+        start_layer: usize,
+        start_idx: usize,
+        end_layer: usize,
+        end_idx: usize,
+        weight: F,
+    ) -> Self {
+        Connection {
+            start_layer,
+            end_layer,
+            start_idx,
+            end_idx,
+            weight,
+            weight_group: None,
+            activation: None,
+            frozen: false,
+        }
+    }
+
+    pub fn start_layer(&self) -> usize {
+        self.start_layer
+    }
+
+    pub fn start_idx(&self) -> usize {
+        self.start_idx
+    }
+
+    pub fn end_layer(&self) -> usize {
+        self.end_layer
+    }
+
+    pub fn end_idx(&self) -> usize {
+        self.end_idx
+    }
+
+    pub fn weight(&self) -> F {
+        self.weight
+    }
+
+    pub fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Compares the endpoints exactly and the weights within `eps` of each other.
+    pub fn approx_eq(&self, other: &Connection<F>, eps: F) -> bool {
+        self.same_endpoints(other) && (self.weight - other.weight).abs() <= eps
+    }
+
+    /// Checks that the connection goes from an input or hidden node to a hidden
+    /// or output node that exists for the given layer sizes.
+    fn fits(&self, sizes: [usize; 3]) -> bool {
+        self.start_layer <= 1
+            && (1..=2).contains(&self.end_layer)
+            && self.start_idx < sizes[self.start_layer]
+            && self.end_idx < sizes[self.end_layer]
+    }
+
+    /// Returns the value this connection adds to its end node when its start
+    /// node holds `value`, after clamping it to `clamp`.
+    fn transmit(&self, activation_funcs: &[&Activation<F>], clamp: Option<(F, F)>, value: F) -> F {
+        let value = clamp.map_or(value, |(min, max)| value.clamp(min, max));
+        let activation = self
+            .activation
+            .unwrap_or(activation_funcs[self.start_layer]);
+
+        activation(value) * self.weight
+    }
+
+    fn allowed_by(&self, allowed_edges: Option<EdgeFilter>) -> bool {
+        allowed_edges.is_none_or(|allowed| {
+            allowed(
+                self.start_layer,
+                self.start_idx,
+                self.end_layer,
+                self.end_idx,
+            )
+        })
+    }
+
+    fn same_endpoints(&self, other: &Connection<F>) -> bool {
+        self.start_layer == other.start_layer
+            && self.start_idx == other.start_idx
+            && self.end_layer == other.end_layer
+            && self.end_idx == other.end_idx
+    }
+}
+
+/// The order `sort_connections` puts connections in, by start layer, end
+/// layer, start index, end index and finally weight.
+///
+/// Sorting by the whole connection makes the order of the floating point sums
+/// in `calculate` depend only on the genome, not on its mutation history.
+fn layer_order<F: TotalOrder + 'static>(a: &Connection<F>, b: &Connection<F>) -> Ordering {
+    (a.start_layer, a.end_layer, a.start_idx, a.end_idx)
+        .cmp(&(b.start_layer, b.end_layer, b.start_idx, b.end_idx))
+        .then(a.weight.total_cmp(&b.weight))
+}
+
+impl<F: fmt::Display + 'static> fmt::Display for Connection<F> {
+    /// Formats as `(start_layer,start_idx) -> (end_layer,end_idx) w=weight`,
+    /// using the formatter's precision for the weight if one is given.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "({},{}) -> ({},{}) w=",
+            self.start_layer, self.start_idx, self.end_layer, self.end_idx
+        )?;
+
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*}", precision, self.weight),
+            None => write!(f, "{}", self.weight),
+        }
+    }
+}
+
+impl<F: Float + TotalOrder + 'static> Agent<'_, F> {
     pub fn create_agents(
         amount: i32,
         inputs: i32,
         outputs: i32,
-        activation_funcs: Vec<&'static dyn Fn(f32) -> f32>,
+        activation_funcs: Vec<&'static Activation<F>>,
+    ) -> Vec<Self> {
+        Agent::create_agents_with_hidden(amount, inputs, 0, outputs, activation_funcs)
+    }
+
+    /// Same as [`Agent::create_agents`], but names the activation functions
+    /// instead of passing them by layer. Input values are transformed by
+    /// `input_activation` and hidden node values by `hidden_activation` before
+    /// they're passed on.
+    pub fn create_agents_with_activations(
+        amount: i32,
+        inputs: i32,
+        outputs: i32,
+        input_activation: &'static Activation<F>,
+        hidden_activation: &'static Activation<F>,
+    ) -> Vec<Self> {
+        Agent::create_agents(
+            amount,
+            inputs,
+            outputs,
+            vec![input_activation, hidden_activation],
+        )
+    }
+
+    /// Same as [`Agent::create_agents`], but starts every agent with `hidden`
+    /// unconnected hidden nodes.
+    pub fn create_agents_with_hidden(
+        amount: i32,
+        inputs: i32,
+        hidden: i32,
+        outputs: i32,
+        activation_funcs: Vec<&'static Activation<F>>,
     ) -> Vec<Self> {
         let mut result: Vec<Self> = vec![];
 
         for _ in 0..amount {
             result.push(Agent {
                 inputs,
-                nodes: 0,
+                nodes: hidden,
                 connections: 0,
                 outputs,
                 data_lists: vec![
-                    vec![0.0; inputs.try_into().unwrap()],
-                    vec![],
-                    vec![0.0; outputs.try_into().unwrap()],
+                    vec![F::zero(); inputs.try_into().unwrap()],
+                    vec![F::zero(); hidden.try_into().unwrap()],
+                    vec![F::zero(); outputs.try_into().unwrap()],
                 ],
                 connection_list: vec![],
                 activation_funcs: activation_funcs.clone(),
+                disabled_inputs: vec![],
+                activation_pool: vec![],
+                activation_clamp: None,
             })
         }
 
-        return result;
+        result
+    }
+
+    /// Builds a single agent from `(start_layer, start_idx, end_layer,
+    /// end_idx, weight)` tuples. The number of hidden nodes is one more than
+    /// the largest hidden index used.
+    pub fn from_edges(
+        inputs: i32,
+        outputs: i32,
+        edges: &[(usize, usize, usize, usize, F)],
+        activation_funcs: Vec<&'static Activation<F>>,
+    ) -> Result<Self, NeatError> {
+        // Node counts are `i32`, so no index may reach `i32::MAX`.
+        if let Some(idx) = edges
+            .iter()
+            .position(|&(_, start_idx, _, end_idx, _)| start_idx.max(end_idx) >= i32::MAX as usize)
+        {
+            return Err(NeatError::InvalidConnection(idx));
+        }
+
+        let nodes = edges
+            .iter()
+            .flat_map(|&(start_layer, start_idx, end_layer, end_idx, _)| {
+                [(start_layer, start_idx), (end_layer, end_idx)]
+            })
+            .filter(|&(layer, _)| layer == 1)
+            .map(|(_, idx)| idx + 1)
+            .max()
+            .unwrap_or(0);
+
+        let mut agent = Agent::create_agents_with_hidden(
+            1,
+            inputs,
+            nodes.try_into().unwrap(),
+            outputs,
+            activation_funcs,
+        )
+        .remove(0);
+        agent.connection_list = edges
+            .iter()
+            .map(|&(start_layer, start_idx, end_layer, end_idx, weight)| {
+                Connection::new(start_layer, start_idx, end_layer, end_idx, weight)
+            })
+            .collect();
+        agent.connections = edges.len().try_into().unwrap();
+        agent.validate()?;
+
+        Ok(agent)
+    }
+
+    pub fn calculate(&mut self, input: &[F]) -> Vec<F> {
+        self.forward(input, true);
+
+        self.data_lists[2].clone()
+    }
+
+    /// Runs the network and hands the values of every layer (inputs, hidden
+    /// nodes and outputs, before activation) to `reduce`, returning its
+    /// result without copying the outputs.
+    pub fn calculate_with<T>(&mut self, input: &[F], mut reduce: impl FnMut(&[Vec<F>]) -> T) -> T {
+        self.forward(input, true);
+
+        reduce(&self.data_lists)
+    }
+
+    /// Returns the outputs of the most recent forward pass.
+    pub fn last_output(&self) -> &[F] {
+        &self.data_lists[2]
+    }
+
+    /// Same as [`Agent::calculate`], but adds to the outputs of the previous
+    /// call instead of starting from zero.
+    ///
+    /// The outputs keep accumulating until [`Agent::reset_activations`] or a
+    /// regular `calculate` call clears them.
+    pub fn calculate_accumulate(&mut self, input: &[F]) -> Vec<F> {
+        self.forward(input, false);
+
+        self.data_lists[2].clone()
+    }
+
+    /// Zeroes the stored values of every layer.
+    pub fn reset_activations(&mut self) {
+        for list in self.data_lists.iter_mut() {
+            list.fill(F::zero());
+        }
     }
 
-    pub fn calculate(&mut self, input: &Vec<f32>) -> Vec<f32> {
+    fn forward(&mut self, input: &[F], reset_outputs: bool) {
         if input.len() != self.inputs.try_into().unwrap() {
             panic!(
                 "Input size ({}) doesn't match target input size ({})",
@@ -60,271 +524,2933 @@ impl Agent<'_> {
                 self.inputs
             );
         } else {
-            self.data_lists[0] = input.to_vec();
+            self.data_lists[0] = self.masked_input(input).collect();
+        }
+
+        if reset_outputs {
+            for idx in 0..self.data_lists[2].len() {
+                self.data_lists[2][idx] = F::zero();
+            }
         }
 
-        for idx in 0..self.data_lists[2].len() {
-            self.data_lists[2][idx] = 0.0;
+        if !self
+            .connection_list
+            .is_sorted_by(|a, b| layer_order(a, b).is_le())
+        {
+            self.sort_connections();
+        }
+
+        if self.nodes == 0 {
+            // Every connection goes straight from an input to an output, so
+            // the hidden layer can be skipped entirely.
+            self.data_lists[1].clear();
+
+            let [inputs, _, outputs] = &mut self.data_lists[..] else {
+                unreachable!()
+            };
+
+            for connection in &self.connection_list {
+                let value = connection.transmit(
+                    &self.activation_funcs,
+                    self.activation_clamp,
+                    inputs[connection.start_idx],
+                );
+                outputs[connection.end_idx] = outputs[connection.end_idx] + value;
+            }
+
+            return;
         }
 
         self.data_lists[1].clear();
 
         for _ in 0..self.nodes {
-            self.data_lists[1].push(0.0);
+            self.data_lists[1].push(F::zero());
         }
 
-        self.sort_connections();
-
         for connection in &self.connection_list {
-            self.data_lists[connection.end_layer][connection.end_idx] += (self.activation_funcs
-                [connection.start_layer])(
+            let value = connection.transmit(
+                &self.activation_funcs,
+                self.activation_clamp,
                 self.data_lists[connection.start_layer][connection.start_idx],
-            ) * connection.weight;
+            );
+            let node = &mut self.data_lists[connection.end_layer][connection.end_idx];
+            *node = *node + value;
         }
-
-        return self.data_lists[2].clone();
     }
 
-    pub fn sort_connections(&mut self) {
-        self.connection_list
-            .sort_by(|a, b| match a.start_layer.cmp(&b.start_layer) {
-                Ordering::Equal => a.end_layer.cmp(&b.end_layer),
-                other => other,
-            });
+    /// Clamps every node value to `(min, max)` before it's passed on, keeping
+    /// activations bounded whatever the weights are. `None` disables it.
+    pub fn set_activation_clamp(&mut self, clamp: Option<(F, F)>) {
+        self.activation_clamp = clamp;
     }
 
-    pub fn reproduce(
-        &self,
-        new_node_chance: f32,
-        new_connection_chance: f32,
-        delete_node_chance: f32,
-        delete_connection_chance: f32,
-        change_weight_chance: f32,
-        change_connection_chance: f32,
-        max_weight: f32,
-    ) -> Self {
-        let mut new_agent = Agent {
-            inputs: self.inputs,
-            nodes: self.nodes,
-            connections: self.connections,
-            outputs: self.outputs,
-            data_lists: self.data_lists.clone(),
-            connection_list: self.connection_list.clone(),
-            activation_funcs: self.activation_funcs.clone(),
-        };
-        let mut rng = thread_rng();
+    /// The activation function applied to input values, the first of the
+    /// layer activation functions.
+    pub fn input_activation(&self) -> Option<&Activation<F>> {
+        self.activation_funcs.first().copied()
+    }
 
-        if rng.gen_range(0.0..1.0) < delete_node_chance && new_agent.nodes > 0 {
-            let idx = rng.gen_range(0..new_agent.nodes);
-            new_agent.nodes -= 1;
+    /// The activation function applied to hidden node values, the second of
+    /// the layer activation functions.
+    pub fn hidden_activation(&self) -> Option<&Activation<F>> {
+        self.activation_funcs.get(1).copied()
+    }
 
-            for connection in new_agent.connection_list.iter_mut() {
-                if connection.start_layer == 1 && connection.start_idx >= idx.try_into().unwrap() {
-                    if connection.start_idx == idx.try_into().unwrap() {
-                        if new_agent.nodes > 0 {
-                            connection.start_idx =
-                                rng.gen_range(0..new_agent.nodes).try_into().unwrap();
-                        } else {
-                            connection.start_layer = 0;
-                            connection.start_idx =
-                                rng.gen_range(0..new_agent.inputs).try_into().unwrap();
-                        }
-                    } else {
-                        connection.start_idx -= 1;
-                    }
-                }
+    pub fn set_input_activation(&mut self, activation: &'static Activation<F>) {
+        self.set_layer_activation(0, activation);
+    }
 
-                if connection.end_layer == 1 && connection.end_idx >= idx.try_into().unwrap() {
-                    if connection.end_idx == idx.try_into().unwrap() {
-                        if new_agent.nodes > 0 {
-                            connection.end_idx =
-                                rng.gen_range(0..new_agent.nodes).try_into().unwrap();
-                        } else {
-                            connection.end_layer = 2;
-                            connection.end_idx =
-                                rng.gen_range(0..new_agent.outputs).try_into().unwrap();
-                        }
-                    } else {
-                        connection.end_idx -= 1;
-                    }
-                }
-            }
+    pub fn set_hidden_activation(&mut self, activation: &'static Activation<F>) {
+        self.set_layer_activation(1, activation);
+    }
 
-            new_agent.data_lists[1].pop();
+    /// Replaces the activation function of `layer`, filling in any missing
+    /// earlier layers with the same function.
+    fn set_layer_activation(&mut self, layer: usize, activation: &'static Activation<F>) {
+        if self.activation_funcs.len() <= layer {
+            self.activation_funcs.resize(layer + 1, activation);
         }
+        self.activation_funcs[layer] = activation;
+    }
 
-        if rng.gen_range(0.0..1.0) < new_node_chance {
-            new_agent.nodes += 1;
+    /// Adds a new, disconnected input to the end of the input layer.
+    pub fn add_input(&mut self) {
+        self.inputs += 1;
+        self.data_lists[0].push(F::zero());
 
-            new_agent.data_lists[1].push(0.0);
+        if !self.disabled_inputs.is_empty() {
+            self.disabled_inputs.push(false);
         }
+    }
 
-        if rng.gen_range(0.0..1.0) < delete_connection_chance && new_agent.connections > 0 {
-            let idx = rng.gen_range(0..new_agent.connections);
-
-            new_agent.connections -= 1;
+    /// Forces every input marked `true` in `mask` to 0 during evaluation,
+    /// whatever value is passed in. An empty mask clears it.
+    pub fn set_input_mask(&mut self, mask: &[bool]) -> Result<(), NeatError> {
+        let inputs: usize = self.inputs.try_into().unwrap();
 
-            new_agent.connection_list.remove(idx.try_into().unwrap());
+        if !mask.is_empty() && mask.len() != inputs {
+            return Err(NeatError::InputSizeMismatch {
+                expected: inputs,
+                found: mask.len(),
+            });
         }
 
-        if rng.gen_range(0.0..1.0) < new_connection_chance {
-            new_agent.connections += 1;
+        self.disabled_inputs = mask.to_vec();
 
-            if new_agent.nodes > 0 {
-                let start_layer = rng.gen_range(0..=1);
-                let start_idx;
+        Ok(())
+    }
 
-                if start_layer == 0 {
-                    start_idx = rng.gen_range(0..new_agent.inputs);
-                } else {
-                    start_idx = rng.gen_range(0..new_agent.nodes);
-                }
+    fn masked_input<'i>(&'i self, input: &'i [F]) -> impl Iterator<Item = F> + 'i {
+        input.iter().enumerate().map(|(idx, value)| {
+            if self.disabled_inputs.get(idx) == Some(&true) {
+                F::zero()
+            } else {
+                *value
+            }
+        })
+    }
 
-                let end_layer = rng.gen_range(1..=2);
-                let end_idx;
+    /// Adds a new, disconnected output to the end of the output layer.
+    pub fn add_output(&mut self) {
+        self.outputs += 1;
+        self.data_lists[2].push(F::zero());
+    }
 
-                if end_layer == 1 {
-                    end_idx = rng.gen_range(0..new_agent.nodes);
-                } else {
-                    end_idx = rng.gen_range(0..new_agent.outputs);
-                }
+    /// Checks that the agent is internally consistent.
+    ///
+    /// Every connection has to point at existing nodes, every source layer
+    /// needs an activation function and the `data_lists` have to match the
+    /// node counts. Useful after building or decoding an agent by hand.
+    pub fn validate(&self) -> Result<(), NeatError> {
+        let sizes: [usize; 3] = [
+            self.inputs.try_into().unwrap(),
+            self.nodes.try_into().unwrap(),
+            self.outputs.try_into().unwrap(),
+        ];
 
-                let new_connection = Connection {
-                    start_layer,
-                    end_layer,
-                    start_idx: start_idx.try_into().unwrap(),
-                    end_idx: end_idx.try_into().unwrap(),
-                    weight: rng.gen_range(-max_weight..max_weight),
-                };
+        for (layer, size) in sizes.iter().enumerate() {
+            if self.data_lists.get(layer).map(|list| list.len()) != Some(*size) {
+                return Err(NeatError::LayerSizeMismatch(layer));
+            }
+        }
 
-                new_agent.connection_list.push(new_connection);
-            } else {
-                let start_layer = 0;
-                let start_idx;
+        if self.connection_list.len() != self.connections.try_into().unwrap() {
+            return Err(NeatError::ConnectionCountMismatch);
+        }
 
-                start_idx = rng.gen_range(0..new_agent.inputs);
+        for (idx, connection) in self.connection_list.iter().enumerate() {
+            if !connection.fits(sizes) {
+                return Err(NeatError::InvalidConnection(idx));
+            }
+            if connection.start_layer >= self.activation_funcs.len() {
+                return Err(NeatError::MissingActivation(connection.start_layer));
+            }
+        }
 
-                let end_layer = 2;
-                let end_idx;
+        Ok(())
+    }
 
-                end_idx = rng.gen_range(0..new_agent.outputs);
+    pub fn connection(&self, idx: usize) -> Option<&Connection<F>> {
+        self.connection_list.get(idx)
+    }
 
-                let new_connection = Connection {
-                    start_layer,
-                    end_layer,
-                    start_idx: start_idx.try_into().unwrap(),
-                    end_idx: end_idx.try_into().unwrap(),
-                    weight: rng.gen_range(-max_weight..max_weight),
-                };
+    pub fn sort_connections(&mut self) {
+        self.connection_list.sort_by(layer_order);
+    }
+}
+
+impl Agent<'_> {
+    /// Brings the agent back into a state [`Agent::validate`] accepts, treating
+    /// the node counts as the truth. Value lists are resized to the counts,
+    /// connection endpoints are clamped into range, connections into an empty
+    /// layer are dropped and the connection counter is recounted. Returns
+    /// whether anything had to change.
+    pub fn repair(&mut self) -> bool {
+        let mut changed = false;
 
-                new_agent.connection_list.push(new_connection);
+        for count in [&mut self.inputs, &mut self.nodes, &mut self.outputs] {
+            if *count < 0 {
+                *count = 0;
+                changed = true;
             }
         }
+        let sizes: [usize; 3] = [
+            self.inputs.try_into().unwrap(),
+            self.nodes.try_into().unwrap(),
+            self.outputs.try_into().unwrap(),
+        ];
 
-        if rng.gen_range(0.0..1.0) < change_connection_chance && new_agent.connections > 0 {
-            let idx: usize = rng.gen_range(0..new_agent.connections).try_into().unwrap();
+        self.data_lists.resize(3, vec![]);
+        for (list, &size) in self.data_lists.iter_mut().zip(&sizes) {
+            if list.len() != size {
+                list.resize(size, 0.0);
+                changed = true;
+            }
+        }
 
-            if new_agent.nodes > 0 {
-                let new_start_layer: usize = rng.gen_range(0..=1);
-                let new_end_layer: usize = rng.gen_range(1..=2);
+        let before = self.connection_list.clone();
+        self.connection_list.retain_mut(|connection| {
+            connection.start_layer = connection.start_layer.min(1);
+            connection.end_layer = connection.end_layer.clamp(1, 2);
+            if sizes[connection.start_layer] == 0 || sizes[connection.end_layer] == 0 {
+                return false;
+            }
+            connection.start_idx = connection.start_idx.min(sizes[connection.start_layer] - 1);
+            connection.end_idx = connection.end_idx.min(sizes[connection.end_layer] - 1);
+            true
+        });
+        changed |= self.connection_list != before;
 
-                new_agent.connection_list[idx].start_layer = new_start_layer;
-                new_agent.connection_list[idx].end_layer = new_end_layer;
-            } else {
-                let new_start_layer: usize = 0;
-                let new_end_layer: usize = 2;
+        let connections = self.connection_list.len().try_into().unwrap();
+        if self.connections != connections {
+            self.connections = connections;
+            changed = true;
+        }
 
-                new_agent.connection_list[idx].start_layer = new_start_layer;
-                new_agent.connection_list[idx].end_layer = new_end_layer;
-            }
+        changed
+    }
 
-            if new_agent.connection_list[idx].start_layer == 0 {
-                let start_idx: usize = rng.gen_range(0..new_agent.inputs).try_into().unwrap();
+    /// Sets the weight of the connection at `idx`, along with every connection
+    /// tied to it.
+    pub fn set_connection_weight(&mut self, idx: usize, weight: f32) -> Result<(), NeatError> {
+        if idx >= self.connection_list.len() {
+            return Err(NeatError::NoSuchConnection(idx));
+        }
 
-                new_agent.connection_list[idx].start_idx = start_idx;
-            } else {
-                let start_idx: usize = rng.gen_range(0..new_agent.nodes).try_into().unwrap();
+        self.set_tied_weight(idx, weight);
 
-                new_agent.connection_list[idx].start_idx = start_idx;
-            }
+        Ok(())
+    }
 
-            if new_agent.connection_list[idx].end_layer == 1 {
-                let end_idx: usize = rng.gen_range(0..new_agent.nodes).try_into().unwrap();
+    /// Adds `delta` to the weight of the connection at `idx`, along with every
+    /// connection tied to it.
+    pub fn perturb_connection_weight(&mut self, idx: usize, delta: f32) -> Result<(), NeatError> {
+        let weight = self
+            .connection_list
+            .get(idx)
+            .ok_or(NeatError::NoSuchConnection(idx))?
+            .weight;
 
-                new_agent.connection_list[idx].end_idx = end_idx;
-            } else {
-                let end_idx: usize = rng.gen_range(0..new_agent.outputs).try_into().unwrap();
+        self.set_tied_weight(idx, weight + delta);
 
-                new_agent.connection_list[idx].end_idx = end_idx;
+        Ok(())
+    }
+
+    /// Protects the connection at `idx` from mutations, or lifts the
+    /// protection. Frozen connections are never removed, moved or reweighted
+    /// by reproduction, and the hidden nodes they touch are never deleted.
+    ///
+    /// Connections sharing a weight group are frozen and unfrozen together,
+    /// since mutating one of them would change the others as well.
+    ///
+    /// Freezing isn't kept by [`Agent::to_bytes`].
+    pub fn set_connection_frozen(&mut self, idx: usize, frozen: bool) -> Result<(), NeatError> {
+        let group = self
+            .connection_list
+            .get(idx)
+            .ok_or(NeatError::NoSuchConnection(idx))?
+            .weight_group;
+
+        for (other, connection) in self.connection_list.iter_mut().enumerate() {
+            if other == idx || (group.is_some() && connection.weight_group == group) {
+                connection.frozen = frozen;
             }
         }
 
-        if rng.gen_range(0.0..1.0) < change_weight_chance && new_agent.connections > 0 {
-            let idx: usize = rng.gen_range(0..new_agent.connections).try_into().unwrap();
+        Ok(())
+    }
+
+    /// Returns whether a frozen connection starts or ends at hidden node `idx`.
+    fn hidden_node_frozen(&self, idx: usize) -> bool {
+        self.connection_list.iter().any(|connection| {
+            connection.frozen
+                && ((connection.start_layer == 1 && connection.start_idx == idx)
+                    || (connection.end_layer == 1 && connection.end_idx == idx))
+        })
+    }
 
-            new_agent.connection_list[idx].weight = rng.gen_range(-max_weight..max_weight);
-        }
+    /// Sets an activation function used by the connection at `idx` instead of
+    /// the one of its start layer, or clears it with `None`.
+    ///
+    /// Overrides aren't kept by [`Agent::to_bytes`].
+    pub fn set_connection_activation(
+        &mut self,
+        idx: usize,
+        activation: Option<&'static Activation>,
+    ) -> Result<(), NeatError> {
+        self.connection_list
+            .get_mut(idx)
+            .ok_or(NeatError::NoSuchConnection(idx))?
+            .activation = activation;
 
-        return new_agent;
+        Ok(())
     }
 
-    pub fn print(&mut self) {
-        self.sort_connections();
-        println!("Nodes: {} ", self.nodes);
-        println!("Connections: {} ", self.connections);
-        println!();
+    /// The activation functions that mutations can assign to connections.
+    /// Empty unless grown with [`Agent::mutate_activation_pool`].
+    pub fn activation_pool(&self) -> &[&'static Activation] {
+        &self.activation_pool
+    }
 
-        for idx in 0..self.connection_list.len() {
-            println!("Connection: {}", idx);
-            println!(
-                "  From   : {}, {}",
-                self.connection_list[idx].start_layer, self.connection_list[idx].start_idx
-            );
-            println!(
-                "  To     : {}, {}",
-                self.connection_list[idx].end_layer, self.connection_list[idx].end_idx
-            );
-            println!("  Weight : {}", self.connection_list[idx].weight);
+    /// Either adds a function from `registry` that isn't pooled yet or removes
+    /// a pooled one. Connections using a removed function fall back to their
+    /// layer's activation.
+    pub fn mutate_activation_pool(&mut self, registry: &[&'static Activation], rng: &mut impl Rng) {
+        let missing: Vec<&'static Activation> = registry
+            .iter()
+            .copied()
+            .filter(|&func| {
+                !self
+                    .activation_pool
+                    .iter()
+                    .any(|&pooled| std::ptr::eq(pooled, func))
+            })
+            .collect();
+
+        if !missing.is_empty() && (self.activation_pool.is_empty() || rng.gen_bool(0.5)) {
+            self.activation_pool
+                .push(missing[rng.gen_range(0..missing.len())]);
+        } else if !self.activation_pool.is_empty() {
+            let removed = self
+                .activation_pool
+                .remove(rng.gen_range(0..self.activation_pool.len()));
+
+            for connection in self.connection_list.iter_mut() {
+                if connection
+                    .activation
+                    .is_some_and(|func| std::ptr::eq(func, removed))
+                {
+                    connection.activation = None;
+                }
+            }
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Redraws every connection weight from `weight_init`, keeping the topology
+    /// and weight groups as they are.
+    pub fn randomize_weights(&mut self, weight_init: &WeightInit, rng: &mut impl Rng) {
+        for idx in 0..self.connection_list.len() {
+            self.set_tied_weight(idx, weight_init.sample(rng));
+        }
+    }
 
-    #[test]
-    fn example_use() {
-        let mut agents = Agent::create_agents(5, 2, 1, vec![TANH, TANH]);
+    /// Returns a copy of the agent with the same topology, whose weights are
+    /// drawn uniformly from the default [`MutationConfig::max_weight`] range.
+    /// The agent itself is left untouched.
+    pub fn clone_with_random_weights(&self, rng: &mut impl Rng) -> Self {
+        let mut twin = self.clone();
+        twin.randomize_weights(
+            &WeightInit::Uniform(MutationConfig::default().max_weight),
+            rng,
+        );
+        twin
+    }
 
-        let mut rng = thread_rng();
-        const EPOCHS: i32 = 10_000;
+    /// Hill climbs the weights for `iterations` steps, each nudging a random
+    /// unfrozen weight by up to `strength` and keeping the change if `fitness`
+    /// doesn't get worse. Returns the best fitness found.
+    ///
+    /// With `lamarckian` the improved weights are written back into the agent,
+    /// so its offspring inherit them. Otherwise only a copy is optimized and
+    /// the agent keeps its genome, which only lets the score guide selection.
+    pub fn optimize_weights(
+        &mut self,
+        iterations: usize,
+        strength: f32,
+        lamarckian: bool,
+        rng: &mut impl Rng,
+        mut fitness: impl FnMut(&mut Self) -> f32,
+    ) -> f32 {
+        let mut best = self.clone();
+        let mut best_score = fitness(&mut best);
+        let unfrozen: Vec<usize> = (0..self.connection_list.len())
+            .filter(|&idx| !self.connection_list[idx].frozen)
+            .collect();
 
-        for epoch in 0..EPOCHS {
-            println!("Epoch: {}", epoch);
+        if unfrozen.is_empty() || strength <= 0.0 {
+            return best_score;
+        }
 
-            let input = vec![rng.gen_range(-1.0..1.0), 1.0];
-            let mut result: Vec<f32> = vec![];
+        for _ in 0..iterations {
+            let mut candidate = best.clone();
+            let idx = unfrozen[rng.gen_range(0..unfrozen.len())];
+            candidate
+                .perturb_connection_weight(idx, rng.gen_range(-strength..strength))
+                .unwrap();
 
-            for agent in agents.iter_mut() {
-                result.push(agent.calculate(&input)[0] / (agent.nodes + 1) as f32);
+            let score = fitness(&mut candidate);
+            if score >= best_score {
+                best = candidate;
+                best_score = score;
             }
+        }
 
-            let index_of_max: usize = result
-                .iter()
-                .enumerate()
-                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
-                .map(|(index, _)| index)
-                .unwrap();
+        if lamarckian {
+            *self = best;
+        }
 
-            let best_agent = agents.remove(index_of_max);
-            agents = vec![best_agent];
-            println!("Best result: {}", result[index_of_max]);
+        best_score
+    }
 
-            for _ in 0..4 {
-                agents.push(agents[0].reproduce(0.1, 0.15, 0.05, 0.05, 0.20, 0.15, 3.0));
-            }
-        }
+    /// Returns the summed length of all connections, counted in layers.
+    ///
+    /// Connections between neighbouring layers and between hidden nodes cost
+    /// 1, while skip connections from an input straight to an output cost 2.
+    /// Subtracting this from the fitness favours locally connected networks.
+    pub fn wiring_cost(&self) -> f32 {
+        self.connection_list
+            .iter()
+            .map(|connection| (connection.end_layer - connection.start_layer).max(1) as f32)
+            .sum()
+    }
+
+    /// Returns the number of operations in one [`Agent::calculate`]: a
+    /// multiply-add per connection plus the activation applications.
+    ///
+    /// Every connection applies its source's activation to the value it
+    /// carries, so a node with several outgoing connections is activated once
+    /// per connection.
+    pub fn flops(&self) -> usize {
+        self.connection_list.len() * 2
+    }
+
+    /// Returns the number of free parameters, which is one weight per
+    /// connection, with every weight group counting once.
+    pub fn num_parameters(&self) -> usize {
+        let mut groups: Vec<usize> = self
+            .connection_list
+            .iter()
+            .filter_map(|connection| connection.weight_group)
+            .collect();
+        let ungrouped = self.connection_list.len() - groups.len();
+
+        groups.sort();
+        groups.dedup();
+
+        ungrouped + groups.len()
+    }
+
+    /// Returns the number of connections relative to the most a feed-forward
+    /// network of this size can have.
+    ///
+    /// The maximum counts every input to hidden, input to output and hidden
+    /// to output pair, plus one direction of every pair of hidden nodes.
+    pub fn density(&self) -> f32 {
+        let inputs: usize = self.inputs.try_into().unwrap();
+        let nodes: usize = self.nodes.try_into().unwrap();
+        let outputs: usize = self.outputs.try_into().unwrap();
+
+        let maximum =
+            inputs * outputs + nodes * (inputs + outputs) + nodes * nodes.saturating_sub(1) / 2;
+        if maximum == 0 {
+            return 0.0;
+        }
+
+        self.connection_list.len() as f32 / maximum as f32
+    }
+
+    /// Returns the weight matrix of the network, indexed as `[from][to]`.
+    ///
+    /// Nodes are numbered with the inputs first, then the hidden nodes and
+    /// then the outputs, so hidden node `i` is row `inputs + i` and output `i`
+    /// is row `inputs + nodes + i`. Parallel connections are summed and
+    /// unconnected pairs are 0.
+    pub fn adjacency_matrix(&self) -> Vec<Vec<f32>> {
+        let inputs: usize = self.inputs.try_into().unwrap();
+        let nodes: usize = self.nodes.try_into().unwrap();
+        let outputs: usize = self.outputs.try_into().unwrap();
+        let offsets = [0, inputs, inputs + nodes];
+        let size = inputs + nodes + outputs;
+
+        let mut matrix = vec![vec![0.0; size]; size];
+
+        for connection in &self.connection_list {
+            matrix[offsets[connection.start_layer] + connection.start_idx]
+                [offsets[connection.end_layer] + connection.end_idx] += connection.weight;
+        }
+
+        matrix
+    }
+
+    /// The layer sizes and the sorted connection endpoints, which identify the
+    /// agent's topology regardless of its weights and connection order.
+    fn structure(&self) -> (i32, i32, i32, Vec<Endpoints>) {
+        let mut endpoints: Vec<_> = self
+            .connection_list
+            .iter()
+            .map(|connection| {
+                (
+                    connection.start_layer,
+                    connection.start_idx,
+                    connection.end_layer,
+                    connection.end_idx,
+                )
+            })
+            .collect();
+        endpoints.sort();
+
+        (self.inputs, self.nodes, self.outputs, endpoints)
+    }
+
+    /// Returns every connection ending at node `idx` of `layer`.
+    pub fn incoming(&self, layer: usize, idx: usize) -> Vec<&Connection> {
+        self.connection_list
+            .iter()
+            .filter(|connection| connection.end_layer == layer && connection.end_idx == idx)
+            .collect()
+    }
+
+    /// Returns the indices of outputs that no connection feeds, which always
+    /// read 0.
+    pub fn disconnected_outputs(&self) -> Vec<usize> {
+        (0..self.outputs.try_into().unwrap())
+            .filter(|&idx| self.incoming(2, idx).is_empty())
+            .collect()
+    }
+
+    /// Returns the indices of connections that share both endpoints with at
+    /// least one other connection, grouped by endpoints.
+    pub fn duplicate_groups(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = vec![];
+
+        for (idx, connection) in self.connection_list.iter().enumerate() {
+            let group = groups
+                .iter_mut()
+                .find(|group| self.connection_list[group[0]].same_endpoints(connection));
+
+            match group {
+                Some(group) => group.push(idx),
+                None => groups.push(vec![idx]),
+            }
+        }
+
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    pub fn has_duplicate_connections(&self) -> bool {
+        !self.duplicate_groups().is_empty()
+    }
+
+    fn duplicate_count(&self) -> usize {
+        self.duplicate_groups()
+            .iter()
+            .map(|group| group.len() - 1)
+            .sum()
+    }
+
+    /// Returns how many distinct connections the add-connection mutation can
+    /// create for the current number of nodes.
+    pub fn possible_connections(&self) -> usize {
+        let inputs: usize = self.inputs.try_into().unwrap();
+        let nodes: usize = self.nodes.try_into().unwrap();
+        let outputs: usize = self.outputs.try_into().unwrap();
+
+        if nodes > 0 {
+            (inputs + nodes) * (nodes + outputs)
+        } else {
+            inputs * outputs
+        }
+    }
+
+    /// Moves one endpoint of connection `idx` to another node, keeping its
+    /// weight. Never creates duplicate connections or cycles between hidden
+    /// nodes, self-loops included.
+    ///
+    /// Returns whether the connection was moved, which is never the case for
+    /// an `idx` past the end of the connection list.
+    pub fn reroute_connection(&mut self, idx: usize, rng: &mut impl Rng) -> bool {
+        self.reroute_connection_within(idx, rng, None)
+    }
+
+    fn reroute_connection_within(
+        &mut self,
+        idx: usize,
+        rng: &mut impl Rng,
+        allowed_edges: Option<EdgeFilter>,
+    ) -> bool {
+        if idx >= self.connection_list.len() {
+            return false;
+        }
+        if self.connection_list.len() - self.duplicate_count() >= self.possible_connections() {
+            return false;
+        }
+
+        let inputs: usize = self.inputs.try_into().unwrap();
+        let nodes: usize = self.nodes.try_into().unwrap();
+        let outputs: usize = self.outputs.try_into().unwrap();
+        let current = self.connection_list[idx];
+
+        let starts = (0..inputs)
+            .map(|idx| (0, idx))
+            .chain((0..nodes).map(|idx| (1, idx)))
+            .map(|(start_layer, start_idx)| Connection {
+                start_layer,
+                start_idx,
+                ..current
+            });
+        let ends = (0..nodes)
+            .map(|idx| (1, idx))
+            .chain((0..outputs).map(|idx| (2, idx)))
+            .map(|(end_layer, end_idx)| Connection {
+                end_layer,
+                end_idx,
+                ..current
+            });
+
+        let candidates: Vec<Connection> = starts
+            .chain(ends)
+            .filter(|candidate| {
+                let creates_cycle = candidate.start_layer == 1
+                    && candidate.end_layer == 1
+                    && self.hidden_path(candidate.end_idx, candidate.start_idx, idx);
+
+                !creates_cycle
+                    && candidate.allowed_by(allowed_edges)
+                    && !self
+                        .connection_list
+                        .iter()
+                        .any(|connection| connection.same_endpoints(candidate))
+            })
+            .collect();
+
+        match candidates.choose(rng) {
+            Some(&candidate) => {
+                self.connection_list[idx] = candidate;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns whether hidden node `to` can be reached from hidden node `from`
+    /// through hidden-to-hidden connections other than `skip`.
+    fn hidden_path(&self, from: usize, to: usize, skip: usize) -> bool {
+        let mut seen = vec![false; self.nodes.try_into().unwrap()];
+        let mut stack = vec![from];
+
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            if std::mem::replace(&mut seen[node], true) {
+                continue;
+            }
+
+            stack.extend(
+                self.connection_list
+                    .iter()
+                    .enumerate()
+                    .filter(|&(idx, connection)| {
+                        idx != skip
+                            && connection.start_layer == 1
+                            && connection.end_layer == 1
+                            && connection.start_idx == node
+                    })
+                    .map(|(_, connection)| connection.end_idx),
+            );
+        }
+
+        false
+    }
+
+    /// Ties the weights of the given connections together, so that weight
+    /// mutations update all of them at once.
+    ///
+    /// Every connection takes the weight of the first one. If any of them is
+    /// frozen, all of them become frozen. Returns the id of the new weight
+    /// group.
+    pub fn tie_weights(&mut self, indices: &[usize]) -> usize {
+        let group = self
+            .connection_list
+            .iter()
+            .filter_map(|connection| connection.weight_group)
+            .max()
+            .map_or(0, |group| group + 1);
+        let weight = self.connection_list[indices[0]].weight;
+        let frozen = indices.iter().any(|&idx| self.connection_list[idx].frozen);
+
+        for &idx in indices {
+            self.connection_list[idx].weight = weight;
+            self.connection_list[idx].weight_group = Some(group);
+            self.connection_list[idx].frozen = frozen;
+        }
+
+        group
+    }
+
+    fn set_tied_weight(&mut self, idx: usize, weight: f32) {
+        match self.connection_list[idx].weight_group {
+            Some(group) => {
+                for connection in self.connection_list.iter_mut() {
+                    if connection.weight_group == Some(group) {
+                        connection.weight = weight;
+                    }
+                }
+            }
+            None => self.connection_list[idx].weight = weight,
+        }
+    }
+
+    /// Returns the index of the largest output for the given input.
+    pub fn decide(&mut self, input: &[f32]) -> usize {
+        self.calculate(input)
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .map(|(index, _)| index)
+            .unwrap()
+    }
+
+    /// Samples an output index from the softmax of the outputs scaled by
+    /// `1 / temperature`.
+    ///
+    /// Low temperatures approach [`Agent::decide`] while high temperatures
+    /// approach a uniform choice. A temperature of 0 or less is the same as
+    /// calling `decide`, and so are outputs the softmax can't be taken of,
+    /// like infinite or NaN values.
+    pub fn sample_action(&mut self, input: &[f32], temperature: f32, rng: &mut impl Rng) -> usize {
+        if temperature <= 0.0 {
+            return self.decide(input);
+        }
+
+        let output = self.calculate(input);
+        let max = output.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        if !max.is_finite() {
+            return self.decide(input);
+        }
+        let weights: Vec<f32> = output
+            .iter()
+            .map(|value| ((value - max) / temperature).exp())
+            .collect();
+
+        let total: f32 = weights.iter().sum();
+        if !total.is_finite() || total <= 0.0 {
+            return self.decide(input);
+        }
+        let mut target = rng.gen_range(0.0..total);
+
+        for (idx, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                return idx;
+            }
+            target -= weight;
+        }
+
+        weights.len() - 1
+    }
+
+    /// Returns an input of the right size with every value drawn uniformly
+    /// from `range`, both ends included.
+    pub fn random_input(&self, range: (f32, f32), rng: &mut impl Rng) -> Vec<f32> {
+        (0..self.inputs)
+            .map(|_| rng.gen_range(range.0..=range.1))
+            .collect()
+    }
+
+    /// Estimates how strongly each output reacts to each input around `input`,
+    /// using central finite differences with step `epsilon`.
+    ///
+    /// The result is indexed as `[input][output]`, like
+    /// [`Agent::adjacency_matrix`].
+    pub fn input_sensitivity(&mut self, input: &[f32], epsilon: f32) -> Vec<Vec<f32>> {
+        let mut point = input.to_vec();
+        let mut sensitivity = Vec::with_capacity(input.len());
+
+        for idx in 0..input.len() {
+            point[idx] = input[idx] + epsilon;
+            let above = self.calculate(&point);
+            point[idx] = input[idx] - epsilon;
+            let below = self.calculate(&point);
+            point[idx] = input[idx];
+
+            sensitivity.push(
+                above
+                    .iter()
+                    .zip(&below)
+                    .map(|(above, below)| (above - below) / (2.0 * epsilon))
+                    .collect(),
+            );
+        }
+
+        sensitivity
+    }
+
+    /// Returns the mean absolute difference between this agent's and
+    /// `other`'s outputs over all `inputs`, or 0 if there are no inputs.
+    pub fn output_diff(&mut self, other: &mut Agent, inputs: &[Vec<f32>]) -> f32 {
+        let mut total = 0.0;
+        let mut count = 0;
+
+        for input in inputs {
+            for (a, b) in self.calculate(input).iter().zip(other.calculate(input)) {
+                total += (a - b).abs();
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            0.0
+        } else {
+            total / count as f32
+        }
+    }
+
+    /// Returns, for every output, the fraction of `inputs` for which it's
+    /// nonzero. Outputs that stay 0 over a whole dataset are dead.
+    pub fn output_activity(&mut self, inputs: &[Vec<f32>]) -> Vec<f32> {
+        let mut active = vec![0; self.outputs.try_into().unwrap()];
+
+        for input in inputs {
+            for (count, value) in active.iter_mut().zip(self.calculate(input)) {
+                if value != 0.0 {
+                    *count += 1;
+                }
+            }
+        }
+
+        active
+            .iter()
+            .map(|&count| {
+                if inputs.is_empty() {
+                    0.0
+                } else {
+                    count as f32 / inputs.len() as f32
+                }
+            })
+            .collect()
+    }
+
+    /// Estimates how much each connection matters by zeroing its weight and
+    /// measuring the [mean absolute output change](Agent::output_diff) over
+    /// `inputs`. Connections scoring close to 0 are safe to prune.
+    ///
+    /// Sorts the connections first, so the result lines up with
+    /// [`Agent::connection`] afterwards.
+    pub fn connection_importance(&mut self, inputs: &[Vec<f32>]) -> Vec<f32> {
+        self.sort_connections();
+
+        (0..self.connection_list.len())
+            .map(|idx| {
+                let mut occluded = self.clone();
+                occluded.connection_list[idx].weight = 0.0;
+
+                self.output_diff(&mut occluded, inputs)
+            })
+            .collect()
+    }
+
+    /// Same as [`Agent::calculate`], but propagates hidden nodes in the given
+    /// order instead of the order of the sorted connection list.
+    ///
+    /// All input connections are applied first, then every hidden node passes
+    /// its value on in `order`. The order has to be a permutation of the
+    /// hidden node indices.
+    pub fn calculate_with_order(
+        &mut self,
+        input: &[f32],
+        order: &[usize],
+    ) -> Result<Vec<f32>, NeatError> {
+        let nodes: usize = self.nodes.try_into().unwrap();
+        let mut seen = vec![false; nodes];
+
+        if order.len() != nodes {
+            return Err(NeatError::InvalidOrder);
+        }
+        for &idx in order {
+            if idx >= nodes || seen[idx] {
+                return Err(NeatError::InvalidOrder);
+            }
+            seen[idx] = true;
+        }
+
+        if input.len() != self.inputs.try_into().unwrap() {
+            panic!(
+                "Input size ({}) doesn't match target input size ({})",
+                input.len(),
+                self.inputs
+            );
+        }
+
+        self.data_lists[0] = self.masked_input(input).collect();
+        self.data_lists[1] = vec![0.0; nodes];
+        self.data_lists[2] = vec![0.0; self.outputs.try_into().unwrap()];
+
+        let sources = std::iter::once((0, None)).chain(order.iter().map(|&idx| (1, Some(idx))));
+
+        for (layer, idx) in sources {
+            for connection in &self.connection_list {
+                if connection.start_layer != layer
+                    || idx.is_some_and(|idx| idx != connection.start_idx)
+                {
+                    continue;
+                }
+
+                self.data_lists[connection.end_layer][connection.end_idx] += connection.transmit(
+                    &self.activation_funcs,
+                    self.activation_clamp,
+                    self.data_lists[connection.start_layer][connection.start_idx],
+                );
+            }
+        }
+
+        Ok(self.data_lists[2].clone())
+    }
+
+    /// Runs the network over a sequence of inputs without resetting the hidden
+    /// state between steps.
+    ///
+    /// Hidden to hidden connections read the hidden values of the previous
+    /// step, which makes them act as recurrent edges. Returns the outputs and
+    /// the hidden values of every step.
+    pub fn calculate_sequence(&mut self, inputs: &[Vec<f32>]) -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
+        let mut outputs = vec![];
+        let mut hidden_states = vec![];
+        let mut previous = vec![0.0; self.nodes.try_into().unwrap()];
+
+        self.sort_connections();
+
+        for input in inputs {
+            if input.len() != self.inputs.try_into().unwrap() {
+                panic!(
+                    "Input size ({}) doesn't match target input size ({})",
+                    input.len(),
+                    self.inputs
+                );
+            }
+
+            self.data_lists[0] = self.masked_input(input).collect();
+            self.data_lists[1] = vec![0.0; previous.len()];
+            self.data_lists[2] = vec![0.0; self.outputs.try_into().unwrap()];
+
+            for connection in &self.connection_list {
+                let value = if connection.start_layer == 1 && connection.end_layer == 1 {
+                    previous[connection.start_idx]
+                } else {
+                    self.data_lists[connection.start_layer][connection.start_idx]
+                };
+
+                self.data_lists[connection.end_layer][connection.end_idx] +=
+                    connection.transmit(&self.activation_funcs, self.activation_clamp, value);
+            }
+
+            previous = self.data_lists[1].clone();
+            outputs.push(self.data_lists[2].clone());
+            hidden_states.push(previous.clone());
+        }
+
+        (outputs, hidden_states)
+    }
+
+    /// Removes every connection whose absolute weight is below `threshold`
+    /// and returns how many were removed. Frozen connections are kept.
+    pub fn prune_weak(&mut self, threshold: f32) -> usize {
+        let before = self.connection_list.len();
+
+        self.connection_list
+            .retain(|connection| connection.frozen || connection.weight.abs() >= threshold);
+        self.connections = self.connection_list.len().try_into().unwrap();
+
+        before - self.connection_list.len()
+    }
+
+    /// Removes hidden nodes that only pass a value on: one incoming and one
+    /// outgoing connection, with an outgoing activation that returns its
+    /// input unchanged. The two connections are merged into one carrying the
+    /// product of their weights, so outputs stay the same.
+    ///
+    /// Frozen and weight-tied connections are left alone. Nothing is collapsed
+    /// while an [activation clamp](Agent::set_activation_clamp) is set, since
+    /// the clamp applies to the hidden node's value too. Returns how many
+    /// nodes were removed.
+    pub fn collapse_passthrough(&mut self) -> usize {
+        let mut removed = 0;
+
+        if self.activation_clamp.is_some() {
+            return removed;
+        }
+
+        while let Some((node, incoming, outgoing)) = self.find_passthrough() {
+            let outgoing = self.connection_list[outgoing];
+            let merged = &mut self.connection_list[incoming];
+            merged.end_layer = outgoing.end_layer;
+            merged.end_idx = outgoing.end_idx;
+            merged.weight *= outgoing.weight;
+
+            self.connection_list
+                .retain(|connection| *connection != outgoing);
+            for connection in self.connection_list.iter_mut() {
+                if connection.start_layer == 1 && connection.start_idx > node {
+                    connection.start_idx -= 1;
+                }
+                if connection.end_layer == 1 && connection.end_idx > node {
+                    connection.end_idx -= 1;
+                }
+            }
+
+            self.nodes -= 1;
+            self.connections = self.connection_list.len().try_into().unwrap();
+            self.data_lists[1].pop();
+            removed += 1;
+        }
+
+        removed
+    }
+
+    /// Returns a passthrough hidden node with the indices of its incoming and
+    /// outgoing connection.
+    fn find_passthrough(&self) -> Option<(usize, usize, usize)> {
+        let is_identity =
+            |func: &Activation| [-2.0, -0.5, 0.0, 0.7, 3.0].iter().all(|&x| func(x) == x);
+        let mergeable =
+            |connection: &Connection| !connection.frozen && connection.weight_group.is_none();
+
+        (0..self.nodes.try_into().unwrap()).find_map(|node| {
+            let touching = |ends: bool| {
+                self.connection_list
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, connection)| {
+                        if ends {
+                            connection.end_layer == 1 && connection.end_idx == node
+                        } else {
+                            connection.start_layer == 1 && connection.start_idx == node
+                        }
+                    })
+                    .map(|(idx, _)| idx)
+                    .collect::<Vec<_>>()
+            };
+            let (incoming, outgoing) = (touching(true), touching(false));
+            let (&[incoming], &[outgoing]) = (&incoming[..], &outgoing[..]) else {
+                return None;
+            };
+            let (first, second) = (
+                &self.connection_list[incoming],
+                &self.connection_list[outgoing],
+            );
+
+            (incoming != outgoing
+                && mergeable(first)
+                && mergeable(second)
+                && is_identity(second.activation.unwrap_or(self.activation_funcs[1])))
+            .then_some((node, incoming, outgoing))
+        })
+    }
+
+    /// Assigns every node its feed-forward layer, numbered like
+    /// [`Agent::adjacency_matrix`]: inputs first, then hidden nodes, then
+    /// outputs.
+    ///
+    /// Inputs are at depth 0 and a hidden node sits one deeper than its
+    /// deepest hidden predecessor, or at 1 without one. Every output sits one
+    /// deeper than the deepest hidden node. Fails if the hidden nodes form a
+    /// cycle.
+    pub fn compute_depths(&self) -> Result<Vec<usize>, NeatError> {
+        let nodes: usize = self.nodes.try_into().unwrap();
+        let hidden_edges: Vec<&Connection> = self
+            .connection_list
+            .iter()
+            .filter(|connection| connection.start_layer == 1 && connection.end_layer == 1)
+            .collect();
+
+        let mut pending = vec![0; nodes];
+        for connection in &hidden_edges {
+            pending[connection.end_idx] += 1;
+        }
+
+        let mut hidden = vec![1; nodes];
+        let mut ready: Vec<usize> = (0..nodes).filter(|&idx| pending[idx] == 0).collect();
+        let mut placed = 0;
+
+        while let Some(node) = ready.pop() {
+            placed += 1;
+
+            for connection in hidden_edges
+                .iter()
+                .filter(|connection| connection.start_idx == node)
+            {
+                hidden[connection.end_idx] = hidden[connection.end_idx].max(hidden[node] + 1);
+                pending[connection.end_idx] -= 1;
+
+                if pending[connection.end_idx] == 0 {
+                    ready.push(connection.end_idx);
+                }
+            }
+        }
+
+        if placed < nodes {
+            return Err(NeatError::Cycle);
+        }
+
+        let output_depth = hidden.iter().max().map_or(1, |depth| depth + 1);
+
+        Ok(std::iter::repeat_n(0, self.inputs.try_into().unwrap())
+            .chain(hidden)
+            .chain(std::iter::repeat_n(
+                output_depth,
+                self.outputs.try_into().unwrap(),
+            ))
+            .collect())
+    }
+
+    /// Returns the number of hidden nodes on the longest input to output path.
+    ///
+    /// A direct input to output connection has a depth of 0. Genomes containing
+    /// a cycle between hidden nodes have no well defined depth and return 0.
+    pub fn depth(&self) -> usize {
+        let nodes: usize = self.nodes.try_into().unwrap();
+        let mut memo: Vec<Option<Option<usize>>> = vec![None; nodes];
+        let mut visiting = vec![false; nodes];
+        let mut result = 0;
+
+        for connection in &self.connection_list {
+            if connection.start_layer != 0 {
+                continue;
+            }
+
+            let depth = if connection.end_layer == 2 {
+                Some(0)
+            } else {
+                match self.depth_from(connection.end_idx, &mut memo, &mut visiting) {
+                    Ok(depth) => depth,
+                    Err(()) => return 0,
+                }
+            };
+
+            if let Some(depth) = depth {
+                result = result.max(depth);
+            }
+        }
+
+        result
+    }
+
+    fn depth_from(
+        &self,
+        idx: usize,
+        memo: &mut [Option<Option<usize>>],
+        visiting: &mut [bool],
+    ) -> Result<Option<usize>, ()> {
+        if let Some(depth) = memo[idx] {
+            return Ok(depth);
+        }
+        if visiting[idx] {
+            return Err(());
+        }
+        visiting[idx] = true;
+
+        let mut result = None;
+
+        for connection in &self.connection_list {
+            if connection.start_layer != 1 || connection.start_idx != idx {
+                continue;
+            }
+
+            let depth = if connection.end_layer == 2 {
+                Some(1)
+            } else {
+                self.depth_from(connection.end_idx, memo, visiting)?
+                    .map(|depth| depth + 1)
+            };
+
+            if depth > result {
+                result = depth;
+            }
+        }
+
+        visiting[idx] = false;
+        memo[idx] = Some(result);
+
+        Ok(result)
+    }
+
+    /// Returns the shortest path length in connections, averaged over every
+    /// input and output pair where the output is reachable from the input.
+    /// Unreachable pairs are left out, and 0 is returned if there are none.
+    pub fn mean_path_length(&self) -> f32 {
+        let inputs: usize = self.inputs.try_into().unwrap();
+        let nodes: usize = self.nodes.try_into().unwrap();
+        let outputs: usize = self.outputs.try_into().unwrap();
+        let offsets = [0, inputs, inputs + nodes];
+
+        let mut next = vec![vec![]; inputs + nodes + outputs];
+        for connection in &self.connection_list {
+            next[offsets[connection.start_layer] + connection.start_idx]
+                .push(offsets[connection.end_layer] + connection.end_idx);
+        }
+
+        let mut total = 0;
+        let mut pairs = 0;
+
+        for input in 0..inputs {
+            let mut distance = vec![None; next.len()];
+            let mut queue = std::collections::VecDeque::from([input]);
+            distance[input] = Some(0);
+
+            while let Some(node) = queue.pop_front() {
+                for &end in &next[node] {
+                    if distance[end].is_none() {
+                        distance[end] = distance[node].map(|length| length + 1);
+                        queue.push_back(end);
+                    }
+                }
+            }
+
+            for length in distance[offsets[2]..].iter().flatten() {
+                total += length;
+                pairs += 1;
+            }
+        }
+
+        if pairs == 0 {
+            return 0.0;
+        }
+
+        total as f32 / pairs as f32
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn reproduce(
+        &self,
+        new_node_chance: f32,
+        new_connection_chance: f32,
+        delete_node_chance: f32,
+        delete_connection_chance: f32,
+        change_weight_chance: f32,
+        change_connection_chance: f32,
+        max_weight: f32,
+    ) -> Self {
+        self.reproduce_with_config(&MutationConfig {
+            new_node_chance,
+            new_connection_chance,
+            delete_node_chance,
+            delete_connection_chance,
+            change_weight_chance,
+            change_connection_chance,
+            max_weight,
+            ..MutationConfig::default()
+        })
+    }
+
+    pub fn reproduce_with_config(&self, config: &MutationConfig) -> Self {
+        self.reproduce_with_rng(config, &mut thread_rng())
+    }
+
+    /// Same as [`Agent::reproduce_with_config`], but draws every mutation from
+    /// `rng` so that offspring can be reproduced from a seed.
+    pub fn reproduce_with_rng<R: Rng>(&self, config: &MutationConfig, rng: &mut R) -> Self {
+        let mut child = self.mutate(config, rng);
+        if !config.force_mutation {
+            return child;
+        }
+
+        for _ in 0..FORCED_MUTATION_ATTEMPTS {
+            if child.nodes != self.nodes || child.connection_list != self.connection_list {
+                return child;
+            }
+            child = self.mutate(config, rng);
+        }
+
+        // The config rarely changes anything, so redraw a weight directly.
+        let unfrozen: Vec<usize> = (0..child.connection_list.len())
+            .filter(|&idx| !child.connection_list[idx].frozen)
+            .collect();
+        if let Some(&idx) = unfrozen.choose(rng) {
+            child.set_tied_weight(idx, rng.gen_range(-config.max_weight..config.max_weight));
+        }
+
+        child
+    }
+
+    /// Same as [`Agent::reproduce_with_rng`], but takes the RNG as a trait
+    /// object, for callers like language bindings that can't name its type.
+    pub fn reproduce_with_dyn_rng(
+        &self,
+        config: &MutationConfig,
+        mut rng: &mut dyn RngCore,
+    ) -> Self {
+        self.reproduce_with_rng(config, &mut rng)
+    }
+
+    /// Applies one round of mutations to a copy of the agent.
+    fn mutate<R: Rng>(&self, config: &MutationConfig, rng: &mut R) -> Self {
+        let mut new_agent = Agent {
+            inputs: self.inputs,
+            nodes: self.nodes,
+            connections: self.connections,
+            outputs: self.outputs,
+            data_lists: self.data_lists.clone(),
+            connection_list: self.connection_list.clone(),
+            activation_funcs: self.activation_funcs.clone(),
+            disabled_inputs: self.disabled_inputs.clone(),
+            activation_pool: self.activation_pool.clone(),
+            activation_clamp: self.activation_clamp,
+        };
+
+        if config.weight_decay != 0.0 {
+            for connection in new_agent.connection_list.iter_mut() {
+                if !connection.frozen {
+                    connection.weight *= 1.0 - config.weight_decay;
+                }
+            }
+        }
+
+        Pipeline::from_config(config).apply(&mut new_agent, rng);
+
+        debug_assert_eq!(new_agent.validate(), Ok(()), "mutation broke the genome");
+
+        new_agent
+    }
+
+    /// Returns the mean absolute weight over all connections, or 0 if there
+    /// are none.
+    pub fn mean_abs_weight(&self) -> f32 {
+        if self.connection_list.is_empty() {
+            return 0.0;
+        }
+
+        self.connection_list
+            .iter()
+            .map(|connection| connection.weight.abs())
+            .sum::<f32>()
+            / self.connection_list.len() as f32
+    }
+
+    pub fn print(&mut self) {
+        self.sort_connections();
+        print!("{}", self.describe(None));
+    }
+
+    /// Same as [`Agent::print`], but rounds weights to `decimals` places and
+    /// leaves the connection list untouched.
+    pub fn print_with_precision(&self, decimals: usize) {
+        print!("{}", self.describe(Some(decimals)));
+    }
+
+    /// Returns the text printed by [`Agent::print`], optionally rounding the
+    /// weights to the given number of decimals.
+    pub fn describe(&self, decimals: Option<usize>) -> String {
+        let mut connections: Vec<&Connection> = self.connection_list.iter().collect();
+        connections.sort_by(|a, b| layer_order(a, b));
+
+        let mut result = format!(
+            "Nodes: {} \nConnections: {} \n\n",
+            self.nodes, self.connections
+        );
+
+        for (idx, connection) in connections.iter().enumerate() {
+            let weight = match decimals {
+                Some(decimals) => format!("{:.*}", decimals, connection.weight),
+                None => connection.weight.to_string(),
+            };
+
+            result += &format!(
+                "Connection: {}\n  From   : {}, {}\n  To     : {}, {}\n  Weight : {}\n",
+                idx,
+                connection.start_layer,
+                connection.start_idx,
+                connection.end_layer,
+                connection.end_idx,
+                weight
+            );
+        }
+
+        result
+    }
+
+    /// Runs the network and draws the inputs, hidden nodes and outputs as three
+    /// lines with one character per node, from ` ` for 0 to `#` for an
+    /// absolute value of 1 or more.
+    pub fn ascii_activation(&mut self, input: &[f32]) -> String {
+        const RAMP: &[u8] = b" .:-=+*#";
+
+        self.calculate_with(input, |layers| {
+            layers
+                .iter()
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(|value| {
+                            let level = value.abs().min(1.0) * (RAMP.len() - 1) as f32;
+                            RAMP[level.round() as usize] as char
+                        })
+                        .collect::<String>()
+                        + "\n"
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the connections as tab separated values, one per line after a
+    /// `start_layer start_idx end_layer end_idx weight` header.
+    pub fn to_edge_list(&self) -> String {
+        let mut result = String::from("start_layer\tstart_idx\tend_layer\tend_idx\tweight\n");
+
+        for connection in &self.connection_list {
+            result += &format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                connection.start_layer,
+                connection.start_idx,
+                connection.end_layer,
+                connection.end_idx,
+                connection.weight
+            );
+        }
+
+        result
+    }
+
+    /// Parses the output of [`Agent::to_edge_list`] back into an agent, like
+    /// [`Agent::from_edges`] does. The header line and blank lines are
+    /// skipped, and an edge that doesn't fit the layers is reported as a
+    /// [`NeatError::MalformedLine`] with its line number.
+    pub fn from_edge_list(
+        text: &str,
+        inputs: i32,
+        outputs: i32,
+        activation_funcs: Vec<&'static Activation>,
+    ) -> Result<Self, NeatError> {
+        let mut edges = vec![];
+        let mut line_numbers = vec![];
+
+        for (idx, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || (idx == 0 && line.starts_with("start_layer")) {
+                continue;
+            }
+
+            let malformed = NeatError::MalformedLine(idx + 1);
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let [start_layer, start_idx, end_layer, end_idx, weight] = fields[..] else {
+                return Err(malformed);
+            };
+            let index = |field: &str| {
+                field
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&idx| idx < i32::MAX as usize)
+                    .ok_or(malformed.clone())
+            };
+
+            edges.push((
+                index(start_layer)?,
+                index(start_idx)?,
+                index(end_layer)?,
+                index(end_idx)?,
+                weight.parse::<f32>().map_err(|_| malformed.clone())?,
+            ));
+            line_numbers.push(idx + 1);
+        }
+
+        Agent::from_edges(inputs, outputs, &edges, activation_funcs).map_err(|err| match err {
+            NeatError::InvalidConnection(edge) => NeatError::MalformedLine(line_numbers[edge]),
+            err => err,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    pub(crate) fn connection(
+        start_layer: usize,
+        start_idx: usize,
+        end_layer: usize,
+        end_idx: usize,
+        weight: f32,
+    ) -> Connection {
+        Connection::new(start_layer, start_idx, end_layer, end_idx, weight)
+    }
+
+    pub(crate) fn agent(
+        inputs: i32,
+        nodes: i32,
+        outputs: i32,
+        connection_list: Vec<Connection>,
+    ) -> Agent<'static> {
+        let mut agent = Agent::create_agents(1, inputs, outputs, vec![TANH, TANH]).remove(0);
+        agent.nodes = nodes;
+        agent.connections = connection_list.len().try_into().unwrap();
+        agent.data_lists[1] = vec![0.0; nodes.try_into().unwrap()];
+        agent.connection_list = connection_list;
+        agent
+    }
+
+    /// A config under which `reproduce` changes nothing.
+    pub(crate) fn no_mutations() -> MutationConfig {
+        MutationConfig {
+            new_node_chance: 0.0,
+            new_connection_chance: 0.0,
+            delete_node_chance: 0.0,
+            delete_connection_chance: 0.0,
+            change_weight_chance: 0.0,
+            change_connection_chance: 0.0,
+            ..MutationConfig::default()
+        }
+    }
+
+    #[test]
+    fn example_use() {
+        let mut agents = Agent::create_agents(5, 2, 1, vec![TANH, TANH]);
+
+        let mut rng = thread_rng();
+        const EPOCHS: i32 = 10_000;
+
+        for epoch in 0..EPOCHS {
+            println!("Epoch: {}", epoch);
+
+            let input = vec![rng.gen_range(-1.0..1.0), 1.0];
+            let mut result: Vec<f32> = vec![];
+
+            for agent in agents.iter_mut() {
+                result.push(agent.calculate(&input)[0] / (agent.nodes + 1) as f32);
+            }
+
+            let index_of_max: usize = result
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+                .map(|(index, _)| index)
+                .unwrap();
+
+            let best_agent = agents.remove(index_of_max);
+            agents = vec![best_agent];
+            println!("Best result: {}", result[index_of_max]);
+
+            for _ in 0..4 {
+                agents.push(agents[0].reproduce(0.1, 0.15, 0.05, 0.05, 0.20, 0.15, 3.0));
+            }
+        }
         agents[0].print();
     }
+
+    #[test]
+    fn depth_of_hidden_chain() {
+        let agent = agent(
+            1,
+            2,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 1.0),
+                connection(1, 0, 1, 1, 1.0),
+                connection(1, 1, 2, 0, 1.0),
+            ],
+        );
+
+        assert_eq!(agent.depth(), 2);
+    }
+
+    #[test]
+    fn depth_of_cyclic_genome_is_zero() {
+        let agent = agent(
+            1,
+            2,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 1.0),
+                connection(1, 0, 1, 1, 1.0),
+                connection(1, 1, 1, 0, 1.0),
+                connection(1, 1, 2, 0, 1.0),
+            ],
+        );
+
+        assert_eq!(agent.depth(), 0);
+    }
+
+    #[test]
+    fn max_nodes_stops_node_growth() {
+        let config = MutationConfig {
+            new_node_chance: 1.0,
+            max_nodes: Some(3),
+            ..no_mutations()
+        };
+        let mut agent = Agent::create_agents(1, 2, 1, vec![TANH, TANH]).remove(0);
+
+        for _ in 0..10 {
+            agent = agent.reproduce_with_config(&config);
+            assert!(agent.nodes <= 3);
+        }
+        assert_eq!(agent.nodes, 3);
+        assert_eq!(agent.data_lists[1].len(), 3);
+    }
+
+    #[test]
+    fn connection_approx_eq() {
+        let a = connection(0, 1, 2, 0, 0.0);
+        let b = connection(0, 1, 2, 0, 1e-9);
+
+        assert!(a.approx_eq(&b, 1e-6));
+        assert_ne!(a, b);
+        assert!(!a.approx_eq(&connection(0, 0, 2, 0, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn recurrent_hidden_state_carries_over() {
+        let mut agent = agent(
+            1,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 1.0),
+                connection(1, 0, 1, 0, 1.0),
+                connection(1, 0, 2, 0, 1.0),
+            ],
+        );
+
+        let (_, excited) = agent.calculate_sequence(&[vec![1.0], vec![0.0]]);
+        let (_, silent) = agent.calculate_sequence(&[vec![0.0], vec![0.0]]);
+
+        assert_eq!(excited[0][0], TANH(1.0));
+        assert_eq!(excited[1][0], TANH(0.0) + TANH(TANH(1.0)));
+        assert_eq!(silent[1][0], 0.0);
+    }
+
+    #[test]
+    fn cold_sampling_matches_decide() {
+        let mut agent = agent(
+            1,
+            0,
+            3,
+            vec![
+                connection(0, 0, 2, 0, 0.2),
+                connection(0, 0, 2, 1, 0.9),
+                connection(0, 0, 2, 2, -0.4),
+            ],
+        );
+        let mut rng = thread_rng();
+        let best = agent.decide(&[1.0]);
+
+        assert_eq!(best, 1);
+        for _ in 0..100 {
+            assert_eq!(agent.sample_action(&[1.0], 1e-4, &mut rng), best);
+        }
+    }
+
+    #[test]
+    fn parallel_connections_are_grouped() {
+        let agent = agent(
+            2,
+            0,
+            1,
+            vec![
+                connection(0, 0, 2, 0, 0.5),
+                connection(0, 1, 2, 0, 0.5),
+                connection(0, 0, 2, 0, -1.0),
+            ],
+        );
+
+        assert!(agent.has_duplicate_connections());
+        assert_eq!(agent.duplicate_groups(), vec![vec![0, 2]]);
+    }
+
+    #[test]
+    fn tied_weights_mutate_together() {
+        let mut parent = agent(
+            3,
+            0,
+            1,
+            vec![
+                connection(0, 0, 2, 0, 0.5),
+                connection(0, 1, 2, 0, -1.0),
+                connection(0, 2, 2, 0, 2.0),
+            ],
+        );
+        parent.tie_weights(&[0, 2]);
+        let config = MutationConfig {
+            change_weight_chance: 1.0,
+            ..no_mutations()
+        };
+
+        for _ in 0..20 {
+            let child = parent.reproduce_with_config(&config);
+            let weights: Vec<f32> = child.connection_list.iter().map(|c| c.weight).collect();
+
+            assert_eq!(weights[0], weights[2]);
+            if weights[0] != 0.5 {
+                assert_eq!(weights[1], -1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn topological_order_fully_propagates() {
+        let mut agent = agent(
+            1,
+            2,
+            1,
+            vec![
+                connection(1, 0, 2, 0, 1.0),
+                connection(1, 1, 1, 0, 1.0),
+                connection(0, 0, 1, 1, 1.0),
+            ],
+        );
+        let expected = TANH(TANH(TANH(1.0)));
+
+        let output = agent.calculate_with_order(&[1.0], &[1, 0]).unwrap();
+        assert!((output[0] - expected).abs() < 1e-6);
+        let output = agent.calculate_with_order(&[1.0], &[0, 1]).unwrap();
+        assert!((output[0] - expected).abs() > 1e-6);
+        assert_eq!(
+            agent.calculate_with_order(&[1.0], &[1, 1]),
+            Err(NeatError::InvalidOrder)
+        );
+    }
+
+    #[test]
+    fn add_connection_on_full_network_is_no_op() {
+        let parent = agent(1, 0, 1, vec![connection(0, 0, 2, 0, 1.0)]);
+        let config = MutationConfig {
+            new_connection_chance: 1.0,
+            ..no_mutations()
+        };
+
+        let child = parent.reproduce_with_config(&config);
+
+        assert_eq!(child.connections, 1);
+        assert_eq!(child.connection_list, parent.connection_list);
+    }
+
+    #[test]
+    fn adaptive_perturbation_scales_with_weights() {
+        let config = MutationConfig {
+            perturb_weight_chance: 1.0,
+            adaptive_perturbation: true,
+            ..no_mutations()
+        };
+        let mean_change = |weight: f32| {
+            let parent = agent(1, 0, 1, vec![connection(0, 0, 2, 0, weight)]);
+            assert_eq!(parent.mean_abs_weight(), weight);
+
+            (0..200)
+                .map(|_| {
+                    (parent.reproduce_with_config(&config).connection_list[0].weight - weight).abs()
+                })
+                .sum::<f32>()
+                / 200.0
+        };
+
+        assert!(mean_change(10.0) > mean_change(1.0) * 5.0);
+    }
+
+    #[test]
+    fn flip_sign_negates_weight() {
+        let parent = agent(1, 0, 1, vec![connection(0, 0, 2, 0, 0.37)]);
+        let config = MutationConfig {
+            flip_sign_chance: 1.0,
+            ..no_mutations()
+        };
+
+        let child = parent.reproduce_with_config(&config);
+
+        assert_eq!(child.connection_list[0].weight, -0.37);
+    }
+
+    #[test]
+    fn calculate_with_sees_every_layer() {
+        let mut agent = agent(
+            2,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 2.0), connection(1, 0, 2, 0, 0.5)],
+        );
+
+        let layers = agent.calculate_with(&[1.0, -1.0], |layers| layers.to_vec());
+
+        assert_eq!(layers[0], vec![1.0, -1.0]);
+        assert_eq!(layers[1], vec![TANH(1.0) * 2.0]);
+        assert_eq!(layers[2], vec![TANH(TANH(1.0) * 2.0) * 0.5]);
+        assert_eq!(
+            agent.calculate_with(&[1.0, -1.0], |layers| layers[1][0]),
+            TANH(1.0) * 2.0
+        );
+    }
+
+    #[test]
+    fn validate_catches_out_of_range_input() {
+        let mut agent = agent(2, 0, 1, vec![connection(0, 1, 2, 0, 1.0)]);
+        assert_eq!(agent.validate(), Ok(()));
+
+        agent.connection_list[0].start_idx = 2;
+        assert_eq!(agent.validate(), Err(NeatError::InvalidConnection(0)));
+    }
+
+    #[test]
+    fn added_input_and_output_start_disconnected() {
+        let mut agent = agent(
+            1,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 0.8), connection(1, 0, 2, 0, 1.3)],
+        );
+        let before = agent.calculate(&[0.6]);
+
+        agent.add_input();
+        agent.add_output();
+
+        assert_eq!(agent.validate(), Ok(()));
+        assert_eq!(agent.calculate(&[0.6, 0.0]), vec![before[0], 0.0]);
+    }
+
+    #[test]
+    fn incoming_lists_connections_into_output() {
+        let agent = agent(
+            2,
+            1,
+            2,
+            vec![
+                connection(0, 0, 2, 0, 0.1),
+                connection(0, 1, 1, 0, 0.2),
+                connection(1, 0, 2, 0, 0.3),
+                connection(0, 1, 2, 1, 0.4),
+            ],
+        );
+
+        let weights: Vec<f32> = agent.incoming(2, 0).iter().map(|c| c.weight()).collect();
+
+        assert_eq!(weights, vec![0.1, 0.3]);
+    }
+
+    #[test]
+    fn accumulate_sums_outputs() {
+        let mut agent = agent(
+            1,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 1.2), connection(1, 0, 2, 0, -0.7)],
+        );
+        let first = agent.calculate(&[0.4])[0];
+        let second = agent.calculate(&[-0.9])[0];
+
+        agent.reset_activations();
+        agent.calculate_accumulate(&[0.4]);
+
+        assert_eq!(agent.calculate_accumulate(&[-0.9]), vec![first + second]);
+    }
+
+    #[test]
+    fn set_and_perturb_connection_weight() {
+        let mut agent = agent(1, 0, 1, vec![connection(0, 0, 2, 0, 1.0)]);
+
+        assert_eq!(agent.set_connection_weight(0, 0.25), Ok(()));
+        assert_eq!(agent.connection(0).unwrap().weight(), 0.25);
+        assert_eq!(agent.perturb_connection_weight(0, 0.5), Ok(()));
+        assert_eq!(agent.connection(0).unwrap().weight(), 0.75);
+        assert_eq!(
+            agent.set_connection_weight(1, 0.0),
+            Err(NeatError::NoSuchConnection(1))
+        );
+    }
+
+    #[test]
+    fn connection_display() {
+        let connection = connection(0, 3, 2, 1, 0.42);
+
+        assert_eq!(connection.to_string(), "(0,3) -> (2,1) w=0.42");
+        assert_eq!(format!("{:.1}", connection), "(0,3) -> (2,1) w=0.4");
+    }
+
+    #[test]
+    fn randomize_weights_keeps_topology() {
+        let mut agent = agent(
+            2,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 0.5),
+                connection(0, 1, 2, 0, 0.5),
+                connection(1, 0, 2, 0, 0.5),
+            ],
+        );
+        let before = agent.connection_list.clone();
+
+        agent.randomize_weights(&WeightInit::Uniform(3.0), &mut thread_rng());
+
+        for (old, new) in before.iter().zip(agent.connection_list.iter()) {
+            assert!(old.same_endpoints(new));
+            assert_ne!(old.weight, new.weight);
+        }
+    }
+
+    #[test]
+    fn adjacency_matrix_layout() {
+        let agent = agent(2, 1, 1, vec![connection(1, 0, 2, 0, 0.75)]);
+
+        let matrix = agent.adjacency_matrix();
+        let filled: Vec<(usize, usize)> = (0..4)
+            .flat_map(|from| (0..4).map(move |to| (from, to)))
+            .filter(|&(from, to)| matrix[from][to] != 0.0)
+            .collect();
+
+        assert_eq!(matrix.len(), 4);
+        assert!(matrix.iter().all(|row| row.len() == 4));
+        assert_eq!(filled, vec![(2, 3)]);
+        assert_eq!(matrix[2][3], 0.75);
+    }
+
+    #[test]
+    fn describe_rounds_weights() {
+        let agent = agent(1, 0, 1, vec![connection(0, 0, 2, 0, 0.123456)]);
+
+        assert!(agent.describe(Some(2)).contains("Weight : 0.12\n"));
+        assert!(agent.describe(None).contains("Weight : 0.123456\n"));
+    }
+
+    #[test]
+    fn activation_override_transmits_absolute_value() {
+        let mut agent = agent(1, 0, 1, vec![connection(0, 0, 2, 0, 2.0)]);
+
+        assert_eq!(agent.calculate(&[-0.5]), vec![TANH(-0.5) * 2.0]);
+        agent.set_connection_activation(0, Some(ABS)).unwrap();
+        assert_eq!(agent.calculate(&[-0.5]), vec![1.0]);
+    }
+
+    #[test]
+    fn density_of_full_networks() {
+        let direct = agent(
+            2,
+            0,
+            1,
+            vec![connection(0, 0, 2, 0, 1.0), connection(0, 1, 2, 0, 1.0)],
+        );
+        let hidden = agent(
+            1,
+            2,
+            1,
+            vec![
+                connection(0, 0, 2, 0, 1.0),
+                connection(0, 0, 1, 0, 1.0),
+                connection(0, 0, 1, 1, 1.0),
+                connection(1, 0, 1, 1, 1.0),
+                connection(1, 0, 2, 0, 1.0),
+                connection(1, 1, 2, 0, 1.0),
+            ],
+        );
+
+        assert_eq!(direct.density(), 1.0);
+        assert_eq!(hidden.density(), 1.0);
+        assert_eq!(
+            agent(2, 0, 2, vec![connection(0, 0, 2, 0, 1.0)]).density(),
+            0.25
+        );
+    }
+
+    #[test]
+    fn create_agents_with_hidden_nodes() {
+        let agents = Agent::create_agents_with_hidden(2, 3, 4, 1, vec![TANH, TANH]);
+
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[0].nodes, 4);
+        assert_eq!(agents[0].data_lists[1].len(), 4);
+        assert_eq!(agents[0].validate(), Ok(()));
+    }
+
+    #[test]
+    fn masked_input_is_ignored() {
+        let mut agent = agent(
+            2,
+            0,
+            1,
+            vec![connection(0, 0, 2, 0, 1.0), connection(0, 1, 2, 0, 0.5)],
+        );
+
+        agent.set_input_mask(&[true, false]).unwrap();
+
+        assert_eq!(agent.calculate(&[0.9, 0.3]), agent.calculate(&[-0.4, 0.3]));
+        assert!((agent.calculate(&[0.9, 0.3])[0] - TANH(0.3) * 0.5).abs() < 1e-6);
+        assert!(agent.set_input_mask(&[true]).is_err());
+    }
+
+    #[test]
+    fn parameters_match_connections() {
+        let mut agent = agent(
+            3,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 1.0),
+                connection(0, 1, 2, 0, 1.0),
+                connection(0, 2, 2, 0, 1.0),
+                connection(1, 0, 2, 0, 1.0),
+            ],
+        );
+        assert_eq!(agent.num_parameters(), agent.connections as usize);
+
+        agent.tie_weights(&[1, 2]);
+        assert_eq!(agent.num_parameters(), 3);
+    }
+
+    #[test]
+    fn weight_decay_shrinks_weights() {
+        let parent = agent(
+            2,
+            0,
+            1,
+            vec![connection(0, 0, 2, 0, 2.0), connection(0, 1, 2, 0, -0.5)],
+        );
+        let config = MutationConfig {
+            weight_decay: 0.1,
+            ..no_mutations()
+        };
+
+        let child = parent.reproduce_with_config(&config);
+
+        assert!((child.connection_list[0].weight - 1.8).abs() < 1e-6);
+        assert!((child.connection_list[1].weight + 0.45).abs() < 1e-6);
+    }
+
+    #[test]
+    fn equivalent_genomes_give_identical_outputs() {
+        let connections = vec![
+            connection(0, 0, 2, 0, 1e8),
+            connection(0, 1, 2, 0, 1.0),
+            connection(0, 2, 2, 0, -1e8),
+        ];
+        let mut shuffled = connections.clone();
+        shuffled.swap(1, 2);
+
+        let mut a = agent(3, 0, 1, connections);
+        let mut b = agent(3, 0, 1, shuffled);
+        let input = [0.5, 0.25, 0.5];
+
+        assert_eq!(
+            a.calculate(&input)[0].to_bits(),
+            b.calculate(&input)[0].to_bits()
+        );
+    }
+
+    #[test]
+    fn skip_connections_cost_more_wiring() {
+        let local = agent(
+            2,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 1.0), connection(1, 0, 2, 0, 1.0)],
+        );
+        let skipping = agent(
+            2,
+            1,
+            1,
+            vec![connection(0, 0, 2, 0, 1.0), connection(0, 1, 2, 0, 1.0)],
+        );
+
+        assert_eq!(local.wiring_cost(), 2.0);
+        assert_eq!(skipping.wiring_cost(), 4.0);
+    }
+
+    #[test]
+    fn no_hidden_fast_path_matches_general_path() {
+        let mut agent = agent(
+            3,
+            0,
+            2,
+            vec![
+                connection(0, 2, 2, 1, -0.3),
+                connection(0, 0, 2, 0, 0.7),
+                connection(0, 1, 2, 0, 1.1),
+                connection(0, 0, 2, 1, 2.5),
+            ],
+        );
+        let input = [0.2, -0.6, 0.9];
+
+        let general = agent.calculate_with_order(&input, &[]).unwrap();
+
+        assert_eq!(agent.calculate(&input), general);
+        assert!(agent.data_lists[1].is_empty());
+    }
+
+    #[test]
+    fn reroute_keeps_weight_and_stays_valid() {
+        for seed in 0..20 {
+            let mut agent = agent(
+                2,
+                2,
+                1,
+                vec![
+                    connection(0, 0, 1, 0, 0.7),
+                    connection(1, 0, 1, 1, 0.2),
+                    connection(1, 1, 2, 0, -0.3),
+                ],
+            );
+            let mut rng = StdRng::seed_from_u64(seed);
+
+            assert!(agent.reroute_connection(2, &mut rng));
+            assert_eq!(agent.connection_list[2].weight, -0.3);
+            assert_ne!(agent.connection_list[2], connection(1, 1, 2, 0, -0.3));
+            assert!(agent.validate().is_ok());
+            assert!(!agent.has_duplicate_connections());
+            assert!(agent
+                .connection_list
+                .iter()
+                .enumerate()
+                .all(|(idx, connection)| {
+                    connection.start_layer != 1
+                        || connection.end_layer != 1
+                        || !agent.hidden_path(connection.end_idx, connection.start_idx, idx)
+                }));
+        }
+    }
+
+    #[test]
+    fn reroute_of_missing_connection_is_refused() {
+        let mut agent = agent(2, 1, 1, vec![connection(0, 0, 1, 0, 0.7)]);
+        let before = agent.clone();
+
+        assert!(!agent.reroute_connection(1, &mut StdRng::seed_from_u64(0)));
+        assert_eq!(agent.connection_list, before.connection_list);
+    }
+
+    #[test]
+    fn linear_sensitivity_is_weight_matrix() {
+        let mut agent = agent(
+            2,
+            0,
+            2,
+            vec![
+                connection(0, 0, 2, 0, 0.5),
+                connection(0, 0, 2, 1, -1.0),
+                connection(0, 1, 2, 1, 2.0),
+            ],
+        );
+        agent.activation_funcs = vec![&|x| x, &|x| x];
+
+        let sensitivity = agent.input_sensitivity(&[0.3, -0.2], 1e-2);
+
+        for (row, expected) in sensitivity.iter().zip(agent.adjacency_matrix()) {
+            for (value, expected) in row.iter().zip(&expected[2..]) {
+                assert!((value - expected).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn connections_only_use_pooled_activations() {
+        let registry: [&'static Activation; 2] = [TANH, ABS];
+        let config = MutationConfig {
+            change_activation_chance: 1.0,
+            ..no_mutations()
+        };
+        let mut agent = agent(
+            2,
+            0,
+            1,
+            vec![connection(0, 0, 2, 0, 1.0), connection(0, 1, 2, 0, 1.0)],
+        );
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for _ in 0..50 {
+            agent.mutate_activation_pool(&registry, &mut rng);
+            agent = agent.reproduce_with_rng(&config, &mut rng);
+
+            for connection in &agent.connection_list {
+                if let Some(func) = connection.activation {
+                    assert!(agent
+                        .activation_pool()
+                        .iter()
+                        .any(|&pooled| std::ptr::eq(pooled, func)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unfed_output_is_disconnected() {
+        let agent = agent(
+            2,
+            1,
+            3,
+            vec![
+                connection(0, 0, 2, 0, 1.0),
+                connection(0, 1, 1, 0, 1.0),
+                connection(1, 0, 2, 2, 1.0),
+            ],
+        );
+
+        assert_eq!(agent.disconnected_outputs(), vec![1]);
+    }
+
+    #[test]
+    fn from_edges_infers_hidden_nodes() {
+        let mut built = Agent::from_edges(
+            2,
+            1,
+            &[(0, 0, 1, 2, 0.5), (1, 2, 2, 0, -1.5), (0, 1, 2, 0, 0.3)],
+            vec![TANH, TANH],
+        )
+        .unwrap();
+        let mut manual = agent(
+            2,
+            3,
+            1,
+            vec![
+                connection(0, 0, 1, 2, 0.5),
+                connection(1, 2, 2, 0, -1.5),
+                connection(0, 1, 2, 0, 0.3),
+            ],
+        );
+
+        assert_eq!(built.nodes, 3);
+        assert_eq!(
+            built.calculate(&[0.4, -0.8]),
+            manual.calculate(&[0.4, -0.8])
+        );
+        assert_eq!(
+            Agent::from_edges(2, 1, &[(0, 2, 2, 0, 1.0)], vec![TANH, TANH]).err(),
+            Some(NeatError::InvalidConnection(0))
+        );
+    }
+
+    #[test]
+    fn banned_skip_connections_are_never_created() {
+        let config = MutationConfig {
+            new_node_chance: 0.3,
+            new_connection_chance: 1.0,
+            delete_node_chance: 0.2,
+            change_connection_chance: 0.5,
+            reroute_connection_chance: 0.5,
+            allowed_edges: Some(|start_layer, _, end_layer, _| start_layer != 0 || end_layer != 2),
+            ..MutationConfig::default()
+        };
+        let mut agent = Agent::create_agents_with_hidden(1, 3, 2, 2, vec![TANH, TANH]).remove(0);
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..200 {
+            agent = agent.reproduce_with_rng(&config, &mut rng);
+
+            assert!(agent
+                .connection_list
+                .iter()
+                .all(|connection| connection.start_layer != 0 || connection.end_layer != 2));
+        }
+        assert!(!agent.connection_list.is_empty());
+    }
+
+    #[test]
+    fn random_weight_clone_keeps_endpoints() {
+        let original = agent(
+            2,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 0.5),
+                connection(0, 1, 1, 0, 0.5),
+                connection(1, 0, 2, 0, 0.5),
+            ],
+        );
+
+        let twin = original.clone_with_random_weights(&mut StdRng::seed_from_u64(2));
+
+        assert_eq!(twin.structure(), original.structure());
+        assert!(original
+            .connection_list
+            .iter()
+            .all(|connection| connection.weight == 0.5));
+        assert!(twin
+            .connection_list
+            .iter()
+            .any(|connection| connection.weight != 0.5));
+    }
+
+    #[test]
+    fn agent_without_outputs_reproduces() {
+        let config = MutationConfig {
+            new_node_chance: 0.5,
+            new_connection_chance: 1.0,
+            delete_node_chance: 0.3,
+            delete_connection_chance: 0.2,
+            change_connection_chance: 0.5,
+            reroute_connection_chance: 0.5,
+            ..MutationConfig::default()
+        };
+        let mut agent = Agent::create_agents(1, 1, 0, vec![TANH, TANH]).remove(0);
+        let mut rng = StdRng::seed_from_u64(11);
+
+        for _ in 0..200 {
+            agent = agent.reproduce_with_rng(&config, &mut rng);
+            assert!(agent.validate().is_ok());
+            assert!(agent.calculate(&[0.5]).is_empty());
+        }
+    }
+
+    #[test]
+    fn clamp_bounds_hidden_value() {
+        let mut agent = agent(
+            1,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 5.0), connection(1, 0, 2, 0, 1.0)],
+        );
+        agent.activation_funcs = vec![&|x| x, &|x| x];
+
+        assert_eq!(agent.calculate(&[1.0]), vec![5.0]);
+
+        agent.set_activation_clamp(Some((-1.0, 1.0)));
+        assert_eq!(agent.calculate(&[1.0]), vec![1.0]);
+        assert_eq!(agent.data_lists[1], vec![5.0]);
+    }
+
+    #[test]
+    fn output_diff_against_clone_and_changed_weight() {
+        let mut original = agent(
+            2,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 0.8), connection(1, 0, 2, 0, -1.2)],
+        );
+        let mut twin = original.clone();
+        let inputs = vec![vec![0.1, 0.2], vec![-0.5, 0.9], vec![1.0, -1.0]];
+
+        assert_eq!(original.output_diff(&mut twin, &inputs), 0.0);
+
+        twin.set_connection_weight(1, 0.0).unwrap();
+        assert!(original.output_diff(&mut twin, &inputs) > 0.0);
+    }
+
+    #[test]
+    fn frozen_connection_survives_mutation() {
+        let config = MutationConfig {
+            new_node_chance: 0.5,
+            new_connection_chance: 0.5,
+            delete_node_chance: 0.5,
+            delete_connection_chance: 0.9,
+            change_weight_chance: 0.5,
+            change_connection_chance: 0.5,
+            perturb_weight_chance: 0.5,
+            flip_sign_chance: 0.5,
+            reroute_connection_chance: 0.5,
+            weight_decay: 0.1,
+            ..no_mutations()
+        };
+        let mut agent = agent(
+            2,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 0.7),
+                connection(0, 1, 2, 0, 0.3),
+                connection(1, 0, 2, 0, -0.4),
+            ],
+        );
+        agent.set_connection_frozen(0, true).unwrap();
+        let frozen = agent.connection_list[0];
+        let mut rng = StdRng::seed_from_u64(4);
+
+        for _ in 0..300 {
+            agent = agent.reproduce_with_rng(&config, &mut rng);
+
+            let kept = agent
+                .connection_list
+                .iter()
+                .filter(|connection| connection.frozen())
+                .collect::<Vec<_>>();
+            assert_eq!(kept, [&frozen]);
+        }
+    }
+
+    #[test]
+    fn random_input_fits_agent() {
+        let agent = agent(5, 0, 1, vec![]);
+        let mut rng = StdRng::seed_from_u64(8);
+
+        for _ in 0..20 {
+            let input = agent.random_input((-0.5, 2.0), &mut rng);
+
+            assert_eq!(input.len(), 5);
+            assert!(input.iter().all(|value| (-0.5..=2.0).contains(value)));
+        }
+    }
+
+    #[test]
+    fn passthrough_node_is_collapsed() {
+        let mut agent = agent(
+            2,
+            2,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 0.5),
+                connection(1, 0, 2, 0, 3.0),
+                connection(0, 1, 1, 1, 1.0),
+                connection(0, 0, 1, 1, -1.0),
+                connection(1, 1, 2, 0, 0.8),
+            ],
+        );
+        agent.activation_funcs = vec![TANH, IDENTITY];
+        let expected = agent.calculate(&[0.3, -0.6]);
+
+        assert_eq!(agent.collapse_passthrough(), 1);
+        assert_eq!(agent.nodes, 1);
+        assert!(agent.validate().is_ok());
+        assert!(agent.connection_list.contains(&connection(0, 0, 2, 0, 1.5)));
+
+        let output = agent.calculate(&[0.3, -0.6]);
+        assert!((output[0] - expected[0]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn clamped_passthrough_node_is_kept() {
+        let mut agent = agent(
+            1,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 4.0), connection(1, 0, 2, 0, 1.0)],
+        );
+        agent.activation_funcs = vec![IDENTITY, IDENTITY];
+        agent.set_activation_clamp(Some((-1.0, 1.0)));
+        let expected = agent.calculate(&[1.0]);
+
+        assert_eq!(agent.collapse_passthrough(), 0);
+        assert_eq!(agent.nodes, 1);
+        assert_eq!(agent.calculate(&[1.0]), expected);
+    }
+
+    #[test]
+    fn diamond_depths() {
+        // i0 -> h0 -> {h1, h2} -> h3 -> o0, plus a skip from i0 to h3.
+        let diamond = agent(
+            1,
+            4,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 1.0),
+                connection(1, 0, 1, 1, 1.0),
+                connection(1, 0, 1, 2, 1.0),
+                connection(1, 1, 1, 3, 1.0),
+                connection(1, 2, 1, 3, 1.0),
+                connection(0, 0, 1, 3, 1.0),
+                connection(1, 3, 2, 0, 1.0),
+            ],
+        );
+
+        assert_eq!(diamond.compute_depths(), Ok(vec![0, 1, 2, 2, 3, 4]));
+
+        let cyclic = agent(
+            1,
+            2,
+            1,
+            vec![connection(1, 0, 1, 1, 1.0), connection(1, 1, 1, 0, 1.0)],
+        );
+        assert_eq!(cyclic.compute_depths(), Err(NeatError::Cycle));
+    }
+
+    #[test]
+    fn forced_mutation_always_changes_offspring() {
+        let config = MutationConfig {
+            change_weight_chance: 0.01,
+            force_mutation: true,
+            ..no_mutations()
+        };
+        let parent = agent(
+            2,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 0.5), connection(1, 0, 2, 0, -0.5)],
+        );
+        let mut rng = StdRng::seed_from_u64(6);
+
+        for _ in 0..50 {
+            let child = parent.reproduce_with_rng(&config, &mut rng);
+
+            assert_ne!(child.connection_list, parent.connection_list);
+        }
+
+        let inert = MutationConfig {
+            force_mutation: true,
+            ..no_mutations()
+        };
+        let child = parent.reproduce_with_rng(&inert, &mut rng);
+        assert_ne!(child.connection_list, parent.connection_list);
+    }
+
+    #[test]
+    fn disconnected_output_is_inactive() {
+        let mut agent = agent(1, 0, 2, vec![connection(0, 0, 2, 0, 1.0)]);
+        let inputs = vec![vec![0.0], vec![0.5], vec![-1.0], vec![2.0]];
+
+        assert_eq!(agent.output_activity(&inputs), vec![0.75, 0.0]);
+    }
+
+    #[test]
+    fn dead_end_connection_is_unimportant() {
+        let mut agent = agent(
+            2,
+            2,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 1.0),
+                connection(1, 0, 2, 0, 2.0),
+                connection(0, 1, 1, 1, 1.5),
+            ],
+        );
+        let inputs = vec![vec![0.5, 0.5], vec![-0.3, 0.9]];
+
+        let importance = agent.connection_importance(&inputs);
+
+        assert_eq!(importance.len(), 3);
+        for (idx, score) in importance.iter().enumerate() {
+            if agent.connection(idx).unwrap().end_layer() == 1
+                && agent.connection(idx).unwrap().end_idx() == 1
+            {
+                assert!(score.abs() < 1e-6);
+            } else {
+                assert!(*score > 0.1);
+            }
+        }
+    }
+
+    #[test]
+    fn reproduction_stress_keeps_indices_in_range() {
+        let config = MutationConfig {
+            new_node_chance: 0.4,
+            new_connection_chance: 0.6,
+            delete_node_chance: 0.4,
+            delete_connection_chance: 0.3,
+            change_connection_chance: 0.4,
+            reroute_connection_chance: 0.3,
+            max_nodes: Some(6),
+            ..MutationConfig::default()
+        };
+
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut agent = Agent::create_agents(1, 3, 2, vec![TANH, TANH]).remove(0);
+
+            for _ in 0..1000 {
+                agent = agent.reproduce_with_rng(&config, &mut rng);
+                agent.calculate(&[0.1, -0.4, 0.9]);
+            }
+        }
+    }
+
+    #[test]
+    fn tanh_saturates_instead_of_nan() {
+        assert!((TANH(100.0) - 1.0).abs() < 1e-6);
+        assert!((TANH(-100.0) + 1.0).abs() < 1e-6);
+
+        for x in [-2.0f32, -0.5, 0.0, 0.3, 1.7] {
+            let e = std::f32::consts::E;
+            let reference = (e.powf(x) - e.powf(-x)) / (e.powf(x) + e.powf(-x));
+            assert!((TANH(x) - reference).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn edge_list_has_one_line_per_connection() {
+        let agent = agent(
+            2,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 0.5),
+                connection(0, 1, 1, 0, -1.0),
+                connection(1, 0, 2, 0, 2.0),
+            ],
+        );
+
+        let edges = agent.to_edge_list();
+        let mut lines = edges.lines();
+
+        assert_eq!(
+            lines.next(),
+            Some("start_layer\tstart_idx\tend_layer\tend_idx\tweight")
+        );
+        assert_eq!(lines.next(), Some("0\t0\t1\t0\t0.5"));
+        assert_eq!(lines.count(), 2);
+    }
+
+    #[test]
+    fn edge_list_round_trip() {
+        let mut original = agent(
+            2,
+            2,
+            1,
+            vec![
+                connection(0, 0, 1, 1, 0.75),
+                connection(1, 1, 2, 0, -1.5),
+                connection(0, 1, 2, 0, 0.125),
+            ],
+        );
+
+        let mut parsed =
+            Agent::from_edge_list(&original.to_edge_list(), 2, 1, vec![TANH, TANH]).unwrap();
+
+        assert_eq!(
+            parsed.calculate(&[0.4, -0.2]),
+            original.calculate(&[0.4, -0.2])
+        );
+        assert_eq!(
+            Agent::from_edge_list("0\t0\t2\t0\t1.0\n0\t1\tx\t0\t1.0", 2, 1, vec![TANH, TANH]).err(),
+            Some(NeatError::MalformedLine(2))
+        );
+    }
+
+    #[test]
+    fn edge_list_errors_name_the_line() {
+        let text = "start_layer\tstart_idx\tend_layer\tend_idx\tweight\n\
+                    0\t0\t2\t0\t1.0\n\
+                    \n\
+                    0\t5\t2\t0\t1.0\n";
+
+        assert_eq!(
+            Agent::from_edge_list(text, 2, 1, vec![TANH, TANH]).err(),
+            Some(NeatError::MalformedLine(4))
+        );
+    }
+
+    #[test]
+    fn flops_hand_count() {
+        // 3 multiply-adds, and i0, i1 and h0 are each activated once per
+        // outgoing connection: 3 activations.
+        let agent = agent(
+            2,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 1.0),
+                connection(0, 1, 1, 0, 1.0),
+                connection(1, 0, 2, 0, 1.0),
+            ],
+        );
+
+        assert_eq!(agent.flops(), 6);
+        assert_eq!(
+            Agent::create_agents(1, 3, 2, vec![TANH, TANH])[0].flops(),
+            0
+        );
+    }
+
+    #[test]
+    fn connected_node_is_wired_in() {
+        let config = MutationConfig {
+            add_connected_node_chance: 1.0,
+            ..no_mutations()
+        };
+        let parent = agent(3, 0, 2, vec![connection(0, 0, 2, 0, 1.0)]);
+
+        let child = parent.reproduce_with_rng(&config, &mut StdRng::seed_from_u64(0));
+
+        assert_eq!(child.nodes, 1);
+        assert_eq!(child.incoming(1, 0).len(), 1);
+        assert!(child
+            .connection_list
+            .iter()
+            .any(|connection| connection.start_layer == 1 && connection.end_layer == 2));
+        assert!(child.validate().is_ok());
+    }
+
+    #[test]
+    fn fast_activations_stay_close() {
+        for step in -500..=500 {
+            let x = step as f32 / 100.0;
+
+            assert!((TANH_FAST(x) - x.tanh()).abs() < 1e-4);
+            assert!((SIGMOID_FAST(x) - 1.0 / (1.0 + (-x).exp())).abs() < 1e-4);
+        }
+        assert_eq!(TANH_FAST(1e30), 1.0);
+        assert_eq!(TANH_FAST(-1e30), -1.0);
+    }
+
+    #[test]
+    fn prune_weak_drops_tiny_weights() {
+        let mut agent = agent(
+            2,
+            0,
+            1,
+            vec![
+                connection(0, 0, 2, 0, 0.001),
+                connection(0, 1, 2, 0, 0.5),
+                connection(0, 0, 2, 0, -0.001),
+                connection(0, 1, 2, 0, 0.002),
+            ],
+        );
+        agent.set_connection_frozen(3, true).unwrap();
+
+        assert_eq!(agent.prune_weak(0.01), 2);
+        assert_eq!(agent.connection_list.len(), 2);
+        assert_eq!(agent.connection_list[0], connection(0, 1, 2, 0, 0.5));
+        assert!(agent.connection_list[1].frozen);
+        assert_eq!(agent.connection_list[1].weight, 0.002);
+        assert!(agent.validate().is_ok());
+    }
+
+    #[test]
+    fn lamarckian_optimization_is_inherited() {
+        let parent = agent(1, 0, 1, vec![connection(0, 0, 2, 0, 0.0)]);
+        let fitness = |agent: &mut Agent| -(agent.calculate(&[1.0])[0] - 0.5).abs();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let mut baldwinian = parent.clone();
+        let score = baldwinian.optimize_weights(200, 0.1, false, &mut rng, fitness);
+        assert!(score > -0.05);
+        assert_eq!(baldwinian.connection_list, parent.connection_list);
+
+        let mut lamarckian = parent.clone();
+        let score = lamarckian.optimize_weights(200, 0.1, true, &mut rng, fitness);
+        let child = lamarckian.reproduce_with_rng(&no_mutations(), &mut rng);
+        assert!(score > -0.05);
+        assert_ne!(child.connection_list, parent.connection_list);
+        assert_eq!(child.connection_list, lamarckian.connection_list);
+    }
+
+    #[test]
+    fn repair_fixes_desynced_agent() {
+        let mut agent = agent(
+            2,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 0.5),
+                connection(1, 0, 2, 0, 1.0),
+                connection(0, 0, 2, 0, 2.0),
+            ],
+        );
+        agent.nodes = 0;
+        agent.connection_list[2].start_idx = 5;
+
+        assert!(agent.repair());
+        assert!(agent.validate().is_ok());
+        assert_eq!(agent.data_lists[1].len(), 0);
+        assert_eq!(agent.connection_list, vec![connection(0, 1, 2, 0, 2.0)]);
+        assert!(!agent.repair());
+        agent.calculate(&[1.0, 1.0]);
+        agent.reproduce_with_rng(&MutationConfig::default(), &mut StdRng::seed_from_u64(2));
+    }
+
+    #[test]
+    fn ascii_activation_shades_by_magnitude() {
+        let mut agent = agent(
+            2,
+            0,
+            2,
+            vec![connection(0, 0, 2, 0, 3.0), connection(0, 1, 2, 1, 0.2)],
+        );
+
+        assert_eq!(agent.ascii_activation(&[1.0, 1.0]), "##\n\n#.\n");
+    }
+
+    #[test]
+    fn reproduce_through_dyn_rng() {
+        let parent = agent(2, 0, 1, vec![]);
+        let config = MutationConfig {
+            new_connection_chance: 1.0,
+            ..no_mutations()
+        };
+        let mut boxed: Box<dyn RngCore> = Box::new(StdRng::seed_from_u64(8));
+
+        let child = parent.reproduce_with_dyn_rng(&config, boxed.as_mut());
+
+        assert_eq!(child.connections, 1);
+        assert_eq!(
+            child.connection_list,
+            parent
+                .reproduce_with_rng(&config, &mut StdRng::seed_from_u64(8))
+                .connection_list
+        );
+    }
+
+    #[test]
+    fn mean_path_length_averages_reachable_pairs() {
+        let agent = agent(
+            2,
+            1,
+            2,
+            vec![
+                connection(0, 0, 2, 0, 1.0),
+                connection(0, 1, 1, 0, 1.0),
+                connection(1, 0, 2, 1, 1.0),
+            ],
+        );
+
+        assert_eq!(agent.mean_path_length(), 1.5);
+        assert_eq!(
+            Agent::create_agents(1, 2, 1, vec![TANH, TANH])[0].mean_path_length(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn inputs_stay_linear_with_tanh_hidden_nodes() {
+        let mut agent = Agent::create_agents_with_activations(1, 1, 2, IDENTITY, TANH).remove(0);
+        agent.nodes = 1;
+        agent.data_lists[1].push(0.0);
+        agent.connection_list = vec![
+            connection(0, 0, 2, 0, 1.0),
+            connection(0, 0, 1, 0, 1.0),
+            connection(1, 0, 2, 1, 1.0),
+        ];
+        agent.connections = 3;
+
+        assert_eq!(agent.calculate(&[2.0]), vec![2.0, 2.0f32.tanh()]);
+
+        agent.set_input_activation(ABS);
+        assert_eq!(agent.calculate(&[-2.0]), vec![2.0, 2.0f32.tanh()]);
+        assert!(std::ptr::eq(agent.hidden_activation().unwrap(), TANH));
+    }
+
+    #[test]
+    fn frozen_weight_groups_stay_frozen() {
+        let config = MutationConfig {
+            change_weight_chance: 1.0,
+            perturb_weight_chance: 1.0,
+            flip_sign_chance: 1.0,
+            weight_decay: 0.1,
+            ..no_mutations()
+        };
+        let mut rng = StdRng::seed_from_u64(4);
+
+        let mut frozen_later = agent(
+            2,
+            0,
+            1,
+            vec![connection(0, 0, 2, 0, 0.5), connection(0, 1, 2, 0, 1.0)],
+        );
+        frozen_later.tie_weights(&[0, 1]);
+        frozen_later.set_connection_frozen(0, true).unwrap();
+
+        let mut tied_later = agent(
+            2,
+            0,
+            1,
+            vec![connection(0, 0, 2, 0, 0.5), connection(0, 1, 2, 0, 1.0)],
+        );
+        tied_later.set_connection_frozen(0, true).unwrap();
+        tied_later.tie_weights(&[1, 0]);
+
+        for parent in [frozen_later, tied_later] {
+            assert!(parent
+                .connection_list
+                .iter()
+                .all(|connection| connection.frozen));
+            for _ in 0..50 {
+                let child = parent.reproduce_with_rng(&config, &mut rng);
+                assert_eq!(child.connection_list, parent.connection_list);
+            }
+        }
+    }
+
+    #[test]
+    fn huge_edge_indices_are_rejected() {
+        let huge = i32::MAX as usize;
+
+        assert_eq!(
+            Agent::from_edges(
+                1,
+                1,
+                &[(0, 0, 2, 0, 1.0), (0, 0, 1, huge, 1.0)],
+                vec![TANH, TANH]
+            )
+            .err(),
+            Some(NeatError::InvalidConnection(1))
+        );
+        assert_eq!(
+            Agent::from_edges(1, 1, &[(1, usize::MAX, 2, 0, 1.0)], vec![TANH, TANH]).err(),
+            Some(NeatError::InvalidConnection(0))
+        );
+        assert_eq!(
+            Agent::from_edge_list("0\t0\t1\t4294967296\t1.0", 1, 1, vec![TANH, TANH]).err(),
+            Some(NeatError::MalformedLine(1))
+        );
+    }
+
+    #[test]
+    fn unbounded_limits_do_not_overflow() {
+        let config = MutationConfig {
+            new_node_chance: 1.0,
+            new_connection_chance: 1.0,
+            add_connected_node_chance: 1.0,
+            max_nodes: Some(usize::MAX),
+            max_connections: Some(usize::MAX),
+            ..no_mutations()
+        };
+        let child =
+            agent(2, 0, 1, vec![]).reproduce_with_rng(&config, &mut StdRng::seed_from_u64(3));
+
+        assert_eq!(child.nodes, 2);
+        assert!(child.connections >= 2);
+    }
+
+    #[test]
+    fn sample_action_survives_non_finite_outputs() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut infinite = agent(
+            1,
+            0,
+            2,
+            vec![
+                connection(0, 0, 2, 0, 1.0),
+                connection(0, 0, 2, 1, f32::INFINITY),
+            ],
+        );
+        let mut nan = agent(
+            1,
+            0,
+            2,
+            vec![
+                connection(0, 0, 2, 0, f32::NAN),
+                connection(0, 0, 2, 1, 1.0),
+            ],
+        );
+
+        assert_eq!(infinite.sample_action(&[1.0], 1.0, &mut rng), 1);
+        assert_eq!(nan.sample_action(&[1.0], 1.0, &mut rng), nan.decide(&[1.0]));
+    }
+
+    #[test]
+    fn f64_agents_sum_more_precisely() {
+        // 1e8 followed by a hundred 1s: every 1 is below half an f32 ulp at 1e8.
+        let edges: Vec<_> = std::iter::once((0, 0, 2, 0, 1e8))
+            .chain((0..100).flat_map(|idx| [(0, 0, 1, idx, 1.0), (1, idx, 2, 0, 1.0)]))
+            .collect();
+
+        let mut single = Agent::<f32>::from_edges(1, 1, &edges, vec![IDENTITY, IDENTITY]).unwrap();
+        let edges: Vec<_> = edges
+            .iter()
+            .map(|&(a, b, c, d, weight)| (a, b, c, d, f64::from(weight)))
+            .collect();
+        let mut double =
+            Agent::<f64>::from_edges(1, 1, &edges, vec![&identity, &identity]).unwrap();
+
+        assert_eq!(single.calculate(&[1.0]), vec![1e8]);
+        assert_eq!(double.calculate(&[1.0]), vec![100_000_100.0]);
+    }
 }
@@ -0,0 +1,138 @@
+use std::fmt::Write;
+
+use crate::{MutationConfig, Population};
+
+/// A summary of an evolution run, enough to document and reproduce it.
+#[derive(Clone, Copy)]
+pub struct Manifest {
+    pub seed: Option<u64>,
+    pub config: MutationConfig,
+    pub generation: usize,
+    pub population_size: usize,
+    /// [`Agent::genome_hash`](crate::Agent::genome_hash) of the best agent
+    /// ever scored.
+    pub champion_hash: Option<u64>,
+}
+
+impl Manifest {
+    /// Serializes the manifest as a JSON object. Unset values are written as
+    /// `null`, and so are NaN, infinite values and the edge filter, which JSON
+    /// can't represent.
+    pub fn to_json(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+        let config = &self.config;
+        let mut json = String::from("{");
+
+        write!(
+            json,
+            "\"seed\":{},\"generation\":{},\"population_size\":{},\"champion_hash\":{},",
+            optional(self.seed.map(|seed| seed.to_string())),
+            self.generation,
+            self.population_size,
+            optional(self.champion_hash.map(|hash| format!("\"{:016x}\"", hash))),
+        )
+        .unwrap();
+
+        let chances = [
+            ("new_node_chance", config.new_node_chance),
+            ("new_connection_chance", config.new_connection_chance),
+            ("delete_node_chance", config.delete_node_chance),
+            ("delete_connection_chance", config.delete_connection_chance),
+            ("change_weight_chance", config.change_weight_chance),
+            ("change_connection_chance", config.change_connection_chance),
+            ("max_weight", config.max_weight),
+            ("perturb_weight_chance", config.perturb_weight_chance),
+            ("perturb_strength", config.perturb_strength),
+            ("flip_sign_chance", config.flip_sign_chance),
+            ("weight_decay", config.weight_decay),
+            (
+                "reroute_connection_chance",
+                config.reroute_connection_chance,
+            ),
+            ("change_activation_chance", config.change_activation_chance),
+            (
+                "add_connected_node_chance",
+                config.add_connected_node_chance,
+            ),
+        ];
+
+        json.push_str("\"config\":{");
+        for (name, value) in chances {
+            let value = value.is_finite().then(|| value.to_string());
+            write!(json, "\"{}\":{},", name, optional(value)).unwrap();
+        }
+        write!(
+            json,
+            "\"max_nodes\":{},\"max_connections\":{},\"max_connection_attempts\":{},\
+             \"adaptive_perturbation\":{},\"force_mutation\":{},\"allowed_edges\":null}}}}",
+            optional(config.max_nodes.map(|max| max.to_string())),
+            optional(config.max_connections.map(|max| max.to_string())),
+            config.max_connection_attempts,
+            config.adaptive_perturbation,
+            config.force_mutation,
+        )
+        .unwrap();
+
+        json
+    }
+}
+
+impl Population<'_> {
+    /// Captures the seed, config, progress and champion of the run.
+    pub fn manifest(&self) -> Manifest {
+        Manifest {
+            seed: self.seed(),
+            config: *self.config(),
+            generation: self.generation(),
+            population_size: self.agents().len(),
+            champion_hash: self.best_ever().map(|champion| champion.genome_hash()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Agent, TANH};
+
+    #[test]
+    fn manifest_describes_run() {
+        let agents = Agent::create_agents(5, 2, 1, vec![TANH, TANH]);
+        let mut population = Population::new(agents, MutationConfig::default());
+        population.set_seed(21);
+        for _ in 0..4 {
+            population.evolve(|agent| agent.calculate(&[1.0, -1.0])[0]);
+        }
+
+        let manifest = population.manifest();
+
+        assert_eq!(
+            manifest.champion_hash,
+            Some(population.best_ever().unwrap().genome_hash())
+        );
+        assert_eq!(manifest.seed, Some(21));
+        assert_eq!(manifest.generation, 4);
+        assert_eq!(manifest.population_size, 5);
+
+        let json = manifest.to_json();
+        assert!(json.starts_with("{\"seed\":21,\"generation\":4,\"population_size\":5,"));
+        assert!(json.contains("\"new_node_chance\":0.1,"));
+        assert!(json.ends_with("\"allowed_edges\":null}}"));
+    }
+
+    #[test]
+    fn non_finite_values_are_null() {
+        let config = MutationConfig {
+            max_weight: f32::INFINITY,
+            weight_decay: f32::NAN,
+            perturb_strength: f32::NEG_INFINITY,
+            ..MutationConfig::default()
+        };
+        let json = Population::new(vec![], config).manifest().to_json();
+
+        assert!(json.contains("\"max_weight\":null,"));
+        assert!(json.contains("\"weight_decay\":null,"));
+        assert!(json.contains("\"perturb_strength\":null,"));
+        assert!(!json.contains("inf") && !json.contains("NaN"));
+    }
+}
@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{Agent, MutationConfig};
+
+/// Returns whether `a` is at least as good as `b` in every objective and
+/// better in at least one. All objectives are maximized.
+fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+/// Splits the indices of `scores` into successive non-dominated fronts.
+fn non_dominated_sort(scores: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let mut dominated_by = vec![0; scores.len()];
+    let mut dominating: Vec<Vec<usize>> = vec![vec![]; scores.len()];
+
+    for a in 0..scores.len() {
+        for b in 0..scores.len() {
+            if dominates(&scores[a], &scores[b]) {
+                dominating[a].push(b);
+            } else if dominates(&scores[b], &scores[a]) {
+                dominated_by[a] += 1;
+            }
+        }
+    }
+
+    let mut fronts = vec![];
+    let mut front: Vec<usize> = (0..scores.len())
+        .filter(|&idx| dominated_by[idx] == 0)
+        .collect();
+
+    while !front.is_empty() {
+        let mut next = vec![];
+        for &a in &front {
+            for &b in &dominating[a] {
+                dominated_by[b] -= 1;
+                if dominated_by[b] == 0 {
+                    next.push(b);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next;
+    }
+
+    fronts
+}
+
+/// Returns the crowding distance of every member of `front`, in front order.
+/// Boundary solutions of each objective get an infinite distance.
+fn crowding_distance(front: &[usize], scores: &[Vec<f32>]) -> Vec<f32> {
+    let mut distance = vec![0.0; front.len()];
+    let columns = (0..scores[front[0]].len()).map(|objective| {
+        front
+            .iter()
+            .map(|&idx| scores[idx][objective])
+            .collect::<Vec<f32>>()
+    });
+
+    for values in columns {
+        let value = |idx: usize| values[idx];
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|&a, &b| value(a).partial_cmp(&value(b)).unwrap_or(Ordering::Equal));
+
+        let (first, last) = (order[0], order[order.len() - 1]);
+        let range = value(last) - value(first);
+        distance[first] = f32::INFINITY;
+        distance[last] = f32::INFINITY;
+
+        if range > 0.0 {
+            for window in order.windows(3) {
+                distance[window[1]] += (value(window[2]) - value(window[0])) / range;
+            }
+        }
+    }
+
+    distance
+}
+
+/// A population evolved towards several objectives at once, NSGA-II style.
+///
+/// Every generation each agent produces one offspring, and the best half of
+/// parents and offspring survives. Agents are ranked by non-dominated front
+/// first and by crowding distance within a front. All objectives are
+/// maximized.
+#[derive(Clone)]
+pub struct MultiObjectivePopulation<'a> {
+    agents: Vec<Agent<'a>>,
+    config: MutationConfig,
+    rng: StdRng,
+}
+
+impl<'a> MultiObjectivePopulation<'a> {
+    pub fn new(agents: Vec<Agent<'a>>, config: MutationConfig) -> Self {
+        MultiObjectivePopulation {
+            agents,
+            config,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn agents(&self) -> &[Agent<'a>] {
+        &self.agents
+    }
+
+    /// Runs one generation, scoring agents with `fitness`.
+    pub fn evolve(&mut self, mut fitness: impl FnMut(&mut Agent<'a>) -> Vec<f32>) {
+        let amount = self.agents.len();
+        let mut candidates = self.agents.clone();
+
+        for agent in &self.agents {
+            candidates.push(agent.reproduce_with_rng(&self.config, &mut self.rng));
+        }
+
+        let scores: Vec<Vec<f32>> = candidates.iter_mut().map(&mut fitness).collect();
+        let mut survivors = vec![];
+
+        for front in non_dominated_sort(&scores) {
+            if survivors.len() + front.len() <= amount {
+                survivors.extend(front);
+                continue;
+            }
+
+            let distance = crowding_distance(&front, &scores);
+            let mut order: Vec<usize> = (0..front.len()).collect();
+            order.sort_by(|&a, &b| {
+                distance[b]
+                    .partial_cmp(&distance[a])
+                    .unwrap_or(Ordering::Equal)
+            });
+
+            survivors.extend(
+                order
+                    .iter()
+                    .take(amount - survivors.len())
+                    .map(|&idx| front[idx]),
+            );
+            break;
+        }
+
+        self.agents = survivors
+            .iter()
+            .map(|&idx| candidates[idx].clone())
+            .collect();
+    }
+
+    /// Scores every agent and returns the non-dominated ones with their
+    /// scores.
+    pub fn pareto_front(
+        &mut self,
+        mut fitness: impl FnMut(&mut Agent<'a>) -> Vec<f32>,
+    ) -> Vec<(Agent<'a>, Vec<f32>)> {
+        let scores: Vec<Vec<f32>> = self.agents.iter_mut().map(&mut fitness).collect();
+
+        match non_dominated_sort(&scores).first() {
+            Some(front) => front
+                .iter()
+                .map(|&idx| (self.agents[idx].clone(), scores[idx].clone()))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TANH;
+
+    #[test]
+    fn pareto_front_has_no_dominated_solutions() {
+        let config = MutationConfig {
+            new_connection_chance: 0.8,
+            ..MutationConfig::default()
+        };
+        let mut population =
+            MultiObjectivePopulation::new(Agent::create_agents(12, 3, 1, vec![TANH, TANH]), config);
+        population.set_seed(9);
+        let fitness = |agent: &mut Agent| {
+            vec![
+                agent.calculate(&[1.0, 1.0, 1.0])[0],
+                -(agent.connections as f32),
+            ]
+        };
+
+        for _ in 0..10 {
+            population.evolve(fitness);
+        }
+        let front = population.pareto_front(fitness);
+
+        assert!(!front.is_empty());
+        for (_, a) in &front {
+            for (_, b) in &front {
+                assert!(!dominates(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn sorting_ranks_fronts() {
+        let scores = vec![
+            vec![1.0, 1.0],
+            vec![2.0, 2.0],
+            vec![3.0, 0.0],
+            vec![0.0, 0.0],
+        ];
+
+        assert_eq!(
+            non_dominated_sort(&scores),
+            vec![vec![1, 2], vec![0], vec![3]]
+        );
+    }
+}
@@ -0,0 +1,644 @@
+use rand::{Rng, RngCore};
+
+use crate::{Agent, Connection, EdgeFilter, MutationConfig};
+
+/// A single change to a genome, applied by [`Pipeline`] with some chance.
+///
+/// Operators only change what they're about and leave the genome valid.
+/// The RNG is a trait object so operators can be boxed and mixed freely.
+pub trait Mutation {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore);
+}
+
+/// Runs each operator with its own chance, in order.
+///
+/// [`Pipeline::from_config`] builds the pipeline [`Agent::reproduce_with_rng`]
+/// applies after weight decay. A pipeline is itself a [`Mutation`], so
+/// pipelines can be nested.
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<(f32, Box<dyn Mutation>)>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    /// Appends `operator`, which is applied with probability `chance`.
+    pub fn with(mut self, chance: f32, operator: impl Mutation + 'static) -> Self {
+        self.steps.push((chance, Box::new(operator)));
+        self
+    }
+
+    /// The operators of reproduction with the chances and limits of `config`.
+    pub fn from_config(config: &MutationConfig) -> Self {
+        Pipeline::new()
+            .with(
+                config.delete_node_chance,
+                DeleteNode {
+                    allowed_edges: config.allowed_edges,
+                },
+            )
+            .with(
+                config.new_node_chance,
+                AddNode {
+                    max_nodes: config.max_nodes,
+                },
+            )
+            .with(
+                config.add_connected_node_chance,
+                AddConnectedNode {
+                    max_nodes: config.max_nodes,
+                    max_connections: config.max_connections,
+                    max_weight: config.max_weight,
+                    allowed_edges: config.allowed_edges,
+                },
+            )
+            .with(config.delete_connection_chance, DeleteConnection)
+            .with(
+                config.new_connection_chance,
+                AddConnection {
+                    max_connections: config.max_connections,
+                    max_attempts: config.max_connection_attempts,
+                    max_weight: config.max_weight,
+                    allowed_edges: config.allowed_edges,
+                },
+            )
+            .with(
+                config.change_connection_chance,
+                ChangeConnection {
+                    allowed_edges: config.allowed_edges,
+                },
+            )
+            .with(
+                config.reroute_connection_chance,
+                RerouteConnection {
+                    allowed_edges: config.allowed_edges,
+                },
+            )
+            .with(config.change_activation_chance, ChangeActivation)
+            .with(
+                config.change_weight_chance,
+                ChangeWeight {
+                    max_weight: config.max_weight,
+                },
+            )
+            .with(
+                config.perturb_weight_chance,
+                PerturbWeight {
+                    strength: config.perturb_strength,
+                    adaptive: config.adaptive_perturbation,
+                },
+            )
+            .with(config.flip_sign_chance, FlipSign)
+    }
+}
+
+impl Mutation for Pipeline {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        for (chance, operator) in &self.steps {
+            if rng.gen_range(0.0..1.0) < *chance {
+                operator.apply(agent, rng);
+            }
+        }
+    }
+}
+
+/// Removes a random hidden node that no frozen connection touches. Its
+/// connections are moved to other nodes, or dropped if there are none.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeleteNode {
+    pub allowed_edges: Option<EdgeFilter>,
+}
+
+impl Mutation for DeleteNode {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        if agent.nodes == 0 {
+            return;
+        }
+        let idx = rng.gen_range(0..agent.nodes);
+        if agent.hidden_node_frozen(idx.try_into().unwrap()) {
+            return;
+        }
+
+        agent.nodes -= 1;
+
+        for connection in agent.connection_list.iter_mut() {
+            if connection.start_layer == 1 && connection.start_idx >= idx.try_into().unwrap() {
+                if connection.start_idx == idx.try_into().unwrap() {
+                    if agent.nodes > 0 {
+                        connection.start_idx = rng.gen_range(0..agent.nodes).try_into().unwrap();
+                    } else {
+                        connection.start_layer = 0;
+                        connection.start_idx = if agent.inputs > 0 {
+                            rng.gen_range(0..agent.inputs).try_into().unwrap()
+                        } else {
+                            0
+                        };
+                    }
+                } else {
+                    connection.start_idx -= 1;
+                }
+            }
+
+            if connection.end_layer == 1 && connection.end_idx >= idx.try_into().unwrap() {
+                if connection.end_idx == idx.try_into().unwrap() {
+                    if agent.nodes > 0 {
+                        connection.end_idx = rng.gen_range(0..agent.nodes).try_into().unwrap();
+                    } else {
+                        connection.end_layer = 2;
+                        connection.end_idx = if agent.outputs > 0 {
+                            rng.gen_range(0..agent.outputs).try_into().unwrap()
+                        } else {
+                            0
+                        };
+                    }
+                } else {
+                    connection.end_idx -= 1;
+                }
+            }
+        }
+
+        // Connections that had nowhere to go are left dangling and dropped
+        // here, together with the ones the edge filter rejects.
+        let sizes = [
+            agent.inputs.try_into().unwrap(),
+            agent.nodes.try_into().unwrap(),
+            agent.outputs.try_into().unwrap(),
+        ];
+        let allowed_edges = self.allowed_edges;
+        agent
+            .connection_list
+            .retain(|connection| connection.fits(sizes) && connection.allowed_by(allowed_edges));
+        agent.connections = agent.connection_list.len().try_into().unwrap();
+        agent.data_lists[1].pop();
+    }
+}
+
+/// Adds a disconnected hidden node, unless there are `max_nodes` already.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AddNode {
+    pub max_nodes: Option<usize>,
+}
+
+impl Mutation for AddNode {
+    fn apply(&self, agent: &mut Agent, _rng: &mut dyn RngCore) {
+        if self
+            .max_nodes
+            .is_none_or(|max| usize::try_from(agent.nodes).unwrap() < max)
+        {
+            agent.nodes += 1;
+
+            agent.data_lists[1].push(0.0);
+        }
+    }
+}
+
+/// Adds a hidden node wired from a random input and to a random output,
+/// unless there are `max_nodes` already, the two connections would exceed
+/// `max_connections` or `allowed_edges` rejects either of them.
+#[derive(Clone, Copy, Debug)]
+pub struct AddConnectedNode {
+    pub max_nodes: Option<usize>,
+    pub max_connections: Option<usize>,
+    pub max_weight: f32,
+    pub allowed_edges: Option<EdgeFilter>,
+}
+
+impl Default for AddConnectedNode {
+    fn default() -> Self {
+        AddConnectedNode {
+            max_nodes: None,
+            max_connections: None,
+            max_weight: MutationConfig::default().max_weight,
+            allowed_edges: None,
+        }
+    }
+}
+
+impl Mutation for AddConnectedNode {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        if agent.inputs == 0
+            || agent.outputs == 0
+            || self
+                .max_nodes
+                .is_some_and(|max| usize::try_from(agent.nodes).unwrap() >= max)
+            || self
+                .max_connections
+                .is_some_and(|max| usize::try_from(agent.connections).unwrap() + 2 > max)
+        {
+            return;
+        }
+
+        let node: usize = agent.nodes.try_into().unwrap();
+        let input: usize = rng.gen_range(0..agent.inputs).try_into().unwrap();
+        let output: usize = rng.gen_range(0..agent.outputs).try_into().unwrap();
+
+        let connections = [(0, input, 1, node), (1, node, 2, output)].map(
+            |(start_layer, start_idx, end_layer, end_idx)| {
+                Connection::new(
+                    start_layer,
+                    start_idx,
+                    end_layer,
+                    end_idx,
+                    rng.gen_range(-self.max_weight..self.max_weight),
+                )
+            },
+        );
+        if !connections
+            .iter()
+            .all(|connection| connection.allowed_by(self.allowed_edges))
+        {
+            return;
+        }
+
+        agent.nodes += 1;
+        agent.data_lists[1].push(0.0);
+        agent.connection_list.extend(connections);
+        agent.connections = agent.connection_list.len().try_into().unwrap();
+    }
+}
+
+/// Removes a random connection that isn't frozen.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeleteConnection;
+
+impl Mutation for DeleteConnection {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        if agent.connections == 0 {
+            return;
+        }
+        let idx: usize = rng.gen_range(0..agent.connections).try_into().unwrap();
+
+        if !agent.connection_list[idx].frozen {
+            agent.connections -= 1;
+
+            agent.connection_list.remove(idx);
+        }
+    }
+}
+
+/// Adds a connection between two nodes that aren't connected yet, trying
+/// `max_attempts` random endpoints before giving up.
+#[derive(Clone, Copy, Debug)]
+pub struct AddConnection {
+    pub max_connections: Option<usize>,
+    pub max_attempts: usize,
+    pub max_weight: f32,
+    pub allowed_edges: Option<EdgeFilter>,
+}
+
+impl Default for AddConnection {
+    fn default() -> Self {
+        let config = MutationConfig::default();
+
+        AddConnection {
+            max_connections: None,
+            max_attempts: config.max_connection_attempts,
+            max_weight: config.max_weight,
+            allowed_edges: None,
+        }
+    }
+}
+
+impl Mutation for AddConnection {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        if self
+            .max_connections
+            .is_some_and(|max| usize::try_from(agent.connections).unwrap() >= max)
+            || agent.connection_list.len() - agent.duplicate_count() >= agent.possible_connections()
+        {
+            return;
+        }
+
+        for _ in 0..self.max_attempts {
+            let new_connection = if agent.nodes > 0 {
+                let start_layer = if agent.inputs > 0 {
+                    rng.gen_range(0..=1)
+                } else {
+                    1
+                };
+                let start_idx = if start_layer == 0 {
+                    rng.gen_range(0..agent.inputs)
+                } else {
+                    rng.gen_range(0..agent.nodes)
+                };
+
+                let end_layer = if agent.outputs > 0 {
+                    rng.gen_range(1..=2)
+                } else {
+                    1
+                };
+                let end_idx = if end_layer == 1 {
+                    rng.gen_range(0..agent.nodes)
+                } else {
+                    rng.gen_range(0..agent.outputs)
+                };
+
+                Connection {
+                    start_layer,
+                    end_layer,
+                    start_idx: start_idx.try_into().unwrap(),
+                    end_idx: end_idx.try_into().unwrap(),
+                    weight: rng.gen_range(-self.max_weight..self.max_weight),
+                    weight_group: None,
+                    activation: None,
+                    frozen: false,
+                }
+            } else {
+                let start_layer = 0;
+                let start_idx = rng.gen_range(0..agent.inputs);
+
+                let end_layer = 2;
+                let end_idx = rng.gen_range(0..agent.outputs);
+
+                Connection {
+                    start_layer,
+                    end_layer,
+                    start_idx: start_idx.try_into().unwrap(),
+                    end_idx: end_idx.try_into().unwrap(),
+                    weight: rng.gen_range(-self.max_weight..self.max_weight),
+                    weight_group: None,
+                    activation: None,
+                    frozen: false,
+                }
+            };
+
+            if new_connection.allowed_by(self.allowed_edges)
+                && !agent
+                    .connection_list
+                    .iter()
+                    .any(|connection| connection.same_endpoints(&new_connection))
+            {
+                agent.connections += 1;
+                agent.connection_list.push(new_connection);
+                break;
+            }
+        }
+    }
+}
+
+/// Moves both endpoints of a random connection that isn't frozen to random
+/// nodes, keeping its weight.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChangeConnection {
+    pub allowed_edges: Option<EdgeFilter>,
+}
+
+impl Mutation for ChangeConnection {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        if agent.connections == 0 {
+            return;
+        }
+        let idx: usize = rng.gen_range(0..agent.connections).try_into().unwrap();
+        let original = agent.connection_list[idx];
+
+        if agent.nodes > 0 {
+            let new_start_layer: usize = if agent.inputs > 0 {
+                rng.gen_range(0..=1)
+            } else {
+                1
+            };
+            let new_end_layer: usize = if agent.outputs > 0 {
+                rng.gen_range(1..=2)
+            } else {
+                1
+            };
+
+            agent.connection_list[idx].start_layer = new_start_layer;
+            agent.connection_list[idx].end_layer = new_end_layer;
+        } else {
+            let new_start_layer: usize = 0;
+            let new_end_layer: usize = 2;
+
+            agent.connection_list[idx].start_layer = new_start_layer;
+            agent.connection_list[idx].end_layer = new_end_layer;
+        }
+
+        if agent.connection_list[idx].start_layer == 0 {
+            let start_idx: usize = rng.gen_range(0..agent.inputs).try_into().unwrap();
+
+            agent.connection_list[idx].start_idx = start_idx;
+        } else {
+            let start_idx: usize = rng.gen_range(0..agent.nodes).try_into().unwrap();
+
+            agent.connection_list[idx].start_idx = start_idx;
+        }
+
+        if agent.connection_list[idx].end_layer == 1 {
+            let end_idx: usize = rng.gen_range(0..agent.nodes).try_into().unwrap();
+
+            agent.connection_list[idx].end_idx = end_idx;
+        } else {
+            let end_idx: usize = rng.gen_range(0..agent.outputs).try_into().unwrap();
+
+            agent.connection_list[idx].end_idx = end_idx;
+        }
+
+        if original.frozen || !agent.connection_list[idx].allowed_by(self.allowed_edges) {
+            agent.connection_list[idx] = original;
+        }
+    }
+}
+
+/// Moves one endpoint of a random connection that isn't frozen, like
+/// [`Agent::reroute_connection`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RerouteConnection {
+    pub allowed_edges: Option<EdgeFilter>,
+}
+
+impl Mutation for RerouteConnection {
+    fn apply(&self, agent: &mut Agent, mut rng: &mut dyn RngCore) {
+        if agent.connections == 0 {
+            return;
+        }
+        let idx = rng.gen_range(0..agent.connection_list.len());
+
+        if !agent.connection_list[idx].frozen {
+            agent.reroute_connection_within(idx, &mut rng, self.allowed_edges);
+        }
+    }
+}
+
+/// Gives a random connection that isn't frozen an activation function from
+/// the agent's [activation pool](Agent::activation_pool).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChangeActivation;
+
+impl Mutation for ChangeActivation {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        if agent.connections == 0 || agent.activation_pool.is_empty() {
+            return;
+        }
+        let idx = rng.gen_range(0..agent.connection_list.len());
+        let func = agent.activation_pool[rng.gen_range(0..agent.activation_pool.len())];
+
+        if !agent.connection_list[idx].frozen {
+            agent.connection_list[idx].activation = Some(func);
+        }
+    }
+}
+
+/// Redraws the weight of a random connection that isn't frozen between
+/// `-max_weight` and `max_weight`.
+#[derive(Clone, Copy, Debug)]
+pub struct ChangeWeight {
+    pub max_weight: f32,
+}
+
+impl Default for ChangeWeight {
+    fn default() -> Self {
+        ChangeWeight {
+            max_weight: MutationConfig::default().max_weight,
+        }
+    }
+}
+
+impl Mutation for ChangeWeight {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        if agent.connections == 0 {
+            return;
+        }
+        let idx: usize = rng.gen_range(0..agent.connections).try_into().unwrap();
+
+        let weight = rng.gen_range(-self.max_weight..self.max_weight);
+
+        if !agent.connection_list[idx].frozen {
+            agent.set_tied_weight(idx, weight);
+        }
+    }
+}
+
+/// Nudges the weight of a random connection that isn't frozen by up to
+/// `strength`, scaled by the agent's mean absolute weight if `adaptive`.
+#[derive(Clone, Copy, Debug)]
+pub struct PerturbWeight {
+    pub strength: f32,
+    pub adaptive: bool,
+}
+
+impl Default for PerturbWeight {
+    fn default() -> Self {
+        let config = MutationConfig::default();
+
+        PerturbWeight {
+            strength: config.perturb_strength,
+            adaptive: config.adaptive_perturbation,
+        }
+    }
+}
+
+impl Mutation for PerturbWeight {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        if agent.connections == 0 {
+            return;
+        }
+        let idx: usize = rng.gen_range(0..agent.connections).try_into().unwrap();
+        let strength = if self.adaptive {
+            self.strength * agent.mean_abs_weight()
+        } else {
+            self.strength
+        };
+
+        if strength > 0.0 && !agent.connection_list[idx].frozen {
+            let weight = agent.connection_list[idx].weight;
+            agent.set_tied_weight(idx, weight + rng.gen_range(-strength..strength));
+        }
+    }
+}
+
+/// Negates the weight of a random connection that isn't frozen.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FlipSign;
+
+impl Mutation for FlipSign {
+    fn apply(&self, agent: &mut Agent, rng: &mut dyn RngCore) {
+        if agent.connections == 0 {
+            return;
+        }
+        let idx: usize = rng.gen_range(0..agent.connections).try_into().unwrap();
+        let weight = agent.connection_list[idx].weight;
+
+        if !agent.connection_list[idx].frozen {
+            agent.set_tied_weight(idx, -weight);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::tests::{agent, connection, no_mutations};
+
+    #[test]
+    fn add_connection_adds_exactly_one() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut agent = agent(3, 2, 2, vec![connection(0, 0, 1, 0, 1.0)]);
+
+        for expected in 2..6 {
+            AddConnection::default().apply(&mut agent, &mut rng);
+
+            assert_eq!(agent.connections, expected);
+            assert_eq!(agent.connection_list.len(), expected as usize);
+            assert!(agent.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn custom_pipeline_only_runs_its_operators() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let parent = agent(2, 0, 1, vec![connection(0, 0, 2, 0, 1.0)]);
+        let pipeline = Pipeline::new()
+            .with(1.0, AddNode { max_nodes: Some(2) })
+            .with(0.0, DeleteConnection)
+            .with(1.0, FlipSign);
+
+        let mut child = parent.clone();
+        for _ in 0..3 {
+            pipeline.apply(&mut child, &mut rng);
+        }
+
+        assert_eq!(child.nodes, 2);
+        assert_eq!(child.connection_list, vec![connection(0, 0, 2, 0, -1.0)]);
+    }
+
+    #[test]
+    fn connected_nodes_respect_connection_limit() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let config = MutationConfig {
+            add_connected_node_chance: 1.0,
+            max_connections: Some(3),
+            ..no_mutations()
+        };
+        let mut child = agent(2, 0, 1, vec![]);
+
+        for _ in 0..5 {
+            child = child.reproduce_with_rng(&config, &mut rng);
+        }
+
+        assert_eq!(child.connections, 2);
+        assert_eq!(child.nodes, 1);
+    }
+
+    #[test]
+    fn filtered_connected_node_is_not_added() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mutation = AddConnectedNode {
+            allowed_edges: Some(|start_layer, _, _, _| start_layer == 1),
+            ..AddConnectedNode::default()
+        };
+        let mut agent = agent(2, 0, 1, vec![connection(0, 0, 2, 0, 1.0)]);
+
+        for _ in 0..5 {
+            mutation.apply(&mut agent, &mut rng);
+        }
+
+        assert_eq!(agent.nodes, 0);
+        assert_eq!(agent.connection_list, vec![connection(0, 0, 2, 0, 1.0)]);
+        assert!(agent.validate().is_ok());
+    }
+}
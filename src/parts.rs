@@ -0,0 +1,108 @@
+use crate::{Activation, Agent, Connection, NeatError};
+
+/// Everything about an agent except its connections, as returned by
+/// [`Agent::take_connections`].
+#[derive(Clone)]
+pub struct AgentMeta<'a> {
+    inputs: i32,
+    nodes: i32,
+    outputs: i32,
+    activation_funcs: Vec<&'a Activation>,
+    disabled_inputs: Vec<bool>,
+    activation_pool: Vec<&'static Activation>,
+    activation_clamp: Option<(f32, f32)>,
+}
+
+impl AgentMeta<'_> {
+    pub fn inputs(&self) -> i32 {
+        self.inputs
+    }
+
+    pub fn nodes(&self) -> i32 {
+        self.nodes
+    }
+
+    pub fn outputs(&self) -> i32 {
+        self.outputs
+    }
+}
+
+impl<'a> Agent<'a> {
+    /// Splits the agent into its connections and everything else, so the
+    /// connections can be edited freely before [`Agent::from_parts`] puts it
+    /// back together.
+    pub fn take_connections(self) -> (AgentMeta<'a>, Vec<Connection>) {
+        let meta = AgentMeta {
+            inputs: self.inputs,
+            nodes: self.nodes,
+            outputs: self.outputs,
+            activation_funcs: self.activation_funcs,
+            disabled_inputs: self.disabled_inputs,
+            activation_pool: self.activation_pool,
+            activation_clamp: self.activation_clamp,
+        };
+
+        (meta, self.connection_list)
+    }
+
+    /// Rebuilds an agent from the parts returned by
+    /// [`Agent::take_connections`], failing if a connection doesn't fit.
+    pub fn from_parts(
+        meta: AgentMeta<'a>,
+        connections: Vec<Connection>,
+    ) -> Result<Self, NeatError> {
+        let agent = Agent {
+            inputs: meta.inputs,
+            nodes: meta.nodes,
+            connections: connections.len().try_into().unwrap(),
+            outputs: meta.outputs,
+            data_lists: vec![
+                vec![0.0; meta.inputs.try_into().unwrap()],
+                vec![0.0; meta.nodes.try_into().unwrap()],
+                vec![0.0; meta.outputs.try_into().unwrap()],
+            ],
+            connection_list: connections,
+            activation_funcs: meta.activation_funcs,
+            disabled_inputs: meta.disabled_inputs,
+            activation_pool: meta.activation_pool,
+            activation_clamp: meta.activation_clamp,
+        };
+        agent.validate()?;
+
+        Ok(agent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{agent, connection};
+
+    #[test]
+    fn take_and_rebuild_keeps_outputs() {
+        let mut original = agent(
+            2,
+            1,
+            1,
+            vec![
+                connection(0, 0, 1, 0, 0.8),
+                connection(0, 1, 1, 0, -0.4),
+                connection(1, 0, 2, 0, 1.3),
+            ],
+        );
+        let expected = original.calculate(&[0.2, 0.7]);
+
+        let (meta, mut connections) = original.take_connections();
+        assert_eq!((meta.inputs(), meta.nodes(), meta.outputs()), (2, 1, 1));
+        connections.reverse();
+        let mut rebuilt = Agent::from_parts(meta.clone(), connections.clone()).unwrap();
+
+        assert_eq!(rebuilt.calculate(&[0.2, 0.7]), expected);
+
+        connections.push(connection(1, 3, 2, 0, 1.0));
+        assert_eq!(
+            Agent::from_parts(meta, connections).err(),
+            Some(NeatError::InvalidConnection(3))
+        );
+    }
+}
@@ -0,0 +1,790 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{layer_order, Agent, MutationConfig, MutationSchedule, RankBias, Selection};
+
+#[derive(Clone)]
+pub struct Population<'a> {
+    agents: Vec<Agent<'a>>,
+    config: MutationConfig,
+    rng: StdRng,
+    pub(crate) generation: usize,
+    /// Best fitness of every evolved generation.
+    pub(crate) history: Vec<f32>,
+    /// The seed last given to [`Population::set_seed`].
+    pub(crate) seed: Option<u64>,
+    /// The best agent ever scored, with its fitness.
+    pub(crate) best_ever: Option<(f32, Agent<'a>)>,
+    /// Spreads offspring over ranked parents instead of only the best one.
+    pub(crate) reproduction_bias: Option<f32>,
+    /// Best agent of every generation, oldest first, when recording is on.
+    pub(crate) champion_history: Option<Vec<Agent<'a>>>,
+    /// How many of the latest champions are kept.
+    pub(crate) champion_limit: Option<usize>,
+}
+
+/// Returns the index of the highest score.
+fn best_index(scores: &[f32]) -> Option<usize> {
+    scores
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map(|(idx, _)| idx)
+}
+
+impl<'a> Population<'a> {
+    pub fn new(agents: Vec<Agent<'a>>, config: MutationConfig) -> Self {
+        Population {
+            agents,
+            config,
+            rng: StdRng::from_entropy(),
+            generation: 0,
+            history: vec![],
+            seed: None,
+            best_ever: None,
+            reproduction_bias: None,
+            champion_history: None,
+            champion_limit: None,
+        }
+    }
+
+    /// Reseeds the RNG used by [`Population::evolve`].
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.seed = Some(seed);
+    }
+
+    /// Returns the seed last given to [`Population::set_seed`], or `None` for
+    /// an entropy seeded population.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn agents(&self) -> &[Agent<'a>] {
+        &self.agents
+    }
+
+    pub fn agents_mut(&mut self) -> &mut Vec<Agent<'a>> {
+        &mut self.agents
+    }
+
+    /// Returns an independent in-memory copy of the population.
+    ///
+    /// The RNG state is copied as well, so evolving the snapshot reproduces
+    /// what evolving the original would have done.
+    pub fn snapshot(&self) -> Population<'a> {
+        self.clone()
+    }
+
+    pub fn config(&self) -> &MutationConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: MutationConfig) {
+        self.config = config;
+    }
+
+    /// Makes [`Population::evolve`] give every agent offspring in proportion to
+    /// its fitness rank, following [`RankBias`], instead of breeding only
+    /// from the best agent. `None` goes back to the best agent only.
+    pub fn set_reproduction_bias(&mut self, bias: Option<f32>) {
+        self.reproduction_bias = bias;
+    }
+
+    /// Returns how many generations have been evolved.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns the best agent scored by any generation so far.
+    pub fn best_ever(&self) -> Option<&Agent<'a>> {
+        self.best_ever.as_ref().map(|(_, agent)| agent)
+    }
+
+    /// Starts recording the best agent of every generation, keeping only the
+    /// latest `limit` of them if given. Champions recorded so far are kept.
+    pub fn record_champions(&mut self, limit: Option<usize>) {
+        self.champion_history.get_or_insert_with(Vec::new);
+        self.champion_limit = limit;
+        self.trim_champions();
+    }
+
+    /// Returns the recorded champions, oldest first. Empty unless recording
+    /// was started with [`Population::record_champions`].
+    pub fn champion_history(&self) -> &[Agent<'a>] {
+        self.champion_history.as_deref().unwrap_or_default()
+    }
+
+    fn trim_champions(&mut self) {
+        if let (Some(history), Some(limit)) = (&mut self.champion_history, self.champion_limit) {
+            let excess = history.len().saturating_sub(limit);
+            history.drain(..excess);
+        }
+    }
+
+    /// Returns the best fitness of every evolved generation, oldest first.
+    pub fn fitness_history(&self) -> &[f32] {
+        &self.history
+    }
+
+    /// Returns the average gain in best fitness per generation over the last
+    /// `window` generations, or 0 without enough history.
+    pub fn improvement_rate(&self, window: usize) -> f32 {
+        let window = window.min(self.history.len().saturating_sub(1));
+        if window == 0 {
+            return 0.0;
+        }
+
+        let last = self.history.len() - 1;
+        (self.history[last] - self.history[last - window]) / window as f32
+    }
+
+    /// Scores every agent with `fitness` without evolving, returning each
+    /// score with the agent's outputs from its last forward pass.
+    pub fn evaluate_and_collect(
+        &mut self,
+        mut fitness: impl FnMut(&mut Agent<'a>) -> f32,
+    ) -> Vec<(f32, Vec<f32>)> {
+        self.agents
+            .iter_mut()
+            .map(|agent| {
+                let score = fitness(agent);
+                (score, agent.last_output().to_vec())
+            })
+            .collect()
+    }
+
+    /// Runs one generation.
+    ///
+    /// Every agent is scored with `fitness`, the best one is kept as the first
+    /// agent and the rest are replaced by its offspring. Returns the best
+    /// fitness, or negative infinity for an empty population.
+    pub fn evolve(&mut self, fitness: impl FnMut(&mut Agent<'a>) -> f32) -> f32 {
+        let config = self.config;
+
+        self.evolve_with_schedule(&mut |_| config, fitness)
+    }
+
+    /// Keeps evolving until `duration` has passed, checking the time between
+    /// generations. Always runs at least one generation and returns how many
+    /// were run.
+    pub fn evolve_for(
+        &mut self,
+        mut fitness: impl FnMut(&mut Agent<'a>) -> f32,
+        duration: Duration,
+    ) -> usize {
+        let start = Instant::now();
+        let mut generations = 0;
+
+        loop {
+            self.evolve(&mut fitness);
+            generations += 1;
+
+            if start.elapsed() >= duration {
+                return generations;
+            }
+        }
+    }
+
+    /// Same as [`Population::evolve`], but asks `schedule` for the mutation
+    /// config to reproduce with, based on this generation's best fitness.
+    ///
+    /// The chosen config is kept as the population's config.
+    pub fn evolve_with_schedule(
+        &mut self,
+        schedule: &mut impl MutationSchedule,
+        fitness: impl FnMut(&mut Agent<'a>) -> f32,
+    ) -> f32 {
+        let scores = self.score(fitness);
+        let Some(best) = best_index(&scores) else {
+            return f32::NEG_INFINITY;
+        };
+
+        self.config = schedule.config(scores[best]);
+        let parents = match self.reproduction_bias {
+            Some(bias) => RankBias { bias }.select(&scores, scores.len() - 1, &mut self.rng),
+            None => vec![best; scores.len() - 1],
+        };
+        self.repopulate(best, &parents);
+
+        scores[best]
+    }
+
+    /// Same as [`Population::evolve`], but lets `selection` pick the parent of
+    /// every offspring instead of always using the best agent.
+    ///
+    /// The best agent is still kept as the first agent.
+    pub fn evolve_with_selection(
+        &mut self,
+        selection: &impl Selection,
+        fitness: impl FnMut(&mut Agent<'a>) -> f32,
+    ) -> f32 {
+        let scores = self.score(fitness);
+        let Some(best) = best_index(&scores) else {
+            return f32::NEG_INFINITY;
+        };
+
+        let parents = selection.select(&scores, scores.len() - 1, &mut self.rng);
+        self.repopulate(best, &parents);
+
+        scores[best]
+    }
+
+    fn score(&mut self, fitness: impl FnMut(&mut Agent<'a>) -> f32) -> Vec<f32> {
+        self.generation += 1;
+
+        let scores: Vec<f32> = self.agents.iter_mut().map(fitness).collect();
+        if let Some(best) = best_index(&scores) {
+            self.history.push(scores[best]);
+
+            if self
+                .best_ever
+                .as_ref()
+                .is_none_or(|(fitness, _)| scores[best] > *fitness)
+            {
+                self.best_ever = Some((scores[best], self.agents[best].clone()));
+            }
+
+            if let Some(history) = &mut self.champion_history {
+                history.push(self.agents[best].clone());
+                self.trim_champions();
+            }
+        }
+
+        scores
+    }
+
+    /// Replaces the agents with the one at `best` followed by one offspring of
+    /// every agent in `parents`.
+    fn repopulate(&mut self, best: usize, parents: &[usize]) {
+        let previous = std::mem::take(&mut self.agents);
+
+        self.agents.push(previous[best].clone());
+
+        for &parent in parents {
+            let child = previous[parent].reproduce_with_rng(&self.config, &mut self.rng);
+            self.agents.push(child);
+        }
+    }
+
+    /// Randomly partitions the agents into two populations, the first holding
+    /// `fraction` of them.
+    ///
+    /// The partition is drawn from a copy of the population's RNG, so it's
+    /// the same for every call on a population with the same seed.
+    pub fn split(&self, fraction: f32) -> (Population<'a>, Population<'a>) {
+        let mut rng = self.rng.clone();
+        let mut agents = self.agents.clone();
+        agents.shuffle(&mut rng);
+
+        let first_len =
+            ((agents.len() as f32 * fraction.clamp(0.0, 1.0)).round() as usize).min(agents.len());
+        let second = agents.split_off(first_len);
+
+        let part = |agents, rng: &mut StdRng| Population {
+            agents,
+            config: self.config,
+            rng: StdRng::from_rng(rng).unwrap(),
+            generation: self.generation,
+            history: self.history.clone(),
+            seed: None,
+            best_ever: self.best_ever.clone(),
+            reproduction_bias: self.reproduction_bias,
+            champion_history: self.champion_history.clone(),
+            champion_limit: self.champion_limit,
+        };
+
+        (part(agents, &mut rng), part(second, &mut rng))
+    }
+
+    /// Scores every agent of both populations with `fitness` and returns
+    /// whether this one's best score is strictly higher.
+    ///
+    /// An empty population never wins.
+    pub fn beats(&self, other: &Population, mut fitness: impl FnMut(&mut Agent) -> f32) -> bool {
+        let mut score = |population: &Population| {
+            population
+                .agents
+                .iter()
+                .map(|agent| fitness(&mut agent.clone()))
+                .fold(f32::NEG_INFINITY, f32::max)
+        };
+
+        score(self) > score(other)
+    }
+
+    /// Returns the Shannon entropy, in bits, of the distribution of distinct
+    /// topologies in the population.
+    ///
+    /// A population of identical structures has an entropy of 0, while `n`
+    /// agents with all different structures reach `log2(n)`.
+    pub fn structural_diversity(&self) -> f32 {
+        let mut counts = HashMap::new();
+        for agent in &self.agents {
+            *counts.entry(agent.structure()).or_insert(0usize) += 1;
+        }
+
+        let total = self.agents.len() as f32;
+
+        counts
+            .values()
+            .map(|&count| {
+                let p = count as f32 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Returns every agent's weights in the canonical connection order used by
+    /// [`Agent::sort_connections`], or `None` if the population is empty or
+    /// the agents don't share one topology.
+    fn aligned_weights(&self) -> Option<Vec<Vec<f32>>> {
+        let structure = self.agents.first()?.structure();
+
+        self.agents
+            .iter()
+            .map(|agent| {
+                if agent.structure() != structure {
+                    return None;
+                }
+
+                let mut connections: Vec<_> = agent.connection_list.iter().collect();
+                connections.sort_by(|a, b| layer_order(a, b));
+                Some(
+                    connections
+                        .iter()
+                        .map(|connection| connection.weight)
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the element-wise mean of the agents' weights, or `None` if the
+    /// population is empty or the agents don't share one topology.
+    ///
+    /// Weights are listed in the canonical connection order used by
+    /// [`Agent::sort_connections`].
+    pub fn weight_centroid(&self) -> Option<Vec<f32>> {
+        let weights = self.aligned_weights()?;
+        let mut centroid = vec![0.0; weights[0].len()];
+
+        for agent in &weights {
+            for (sum, weight) in centroid.iter_mut().zip(agent) {
+                *sum += weight;
+            }
+        }
+
+        let count = weights.len() as f32;
+        Some(centroid.iter().map(|sum| sum / count).collect())
+    }
+
+    /// Returns the Pearson correlation of every pair of weights across the
+    /// agents, or `None` if the population is empty or the agents don't share
+    /// one topology. Rows and columns follow the order of
+    /// [`Population::weight_centroid`], and weights that never vary have a
+    /// correlation of 0 with everything.
+    pub fn weight_correlation_matrix(&self) -> Option<Vec<Vec<f32>>> {
+        let weights = self.aligned_weights()?;
+        let centroid = self.weight_centroid()?;
+        let deviation = |agent: &Vec<f32>, idx: usize| agent[idx] - centroid[idx];
+
+        let correlation = |a: usize, b: usize| {
+            let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+
+            for agent in &weights {
+                covariance += deviation(agent, a) * deviation(agent, b);
+                variance_a += deviation(agent, a).powi(2);
+                variance_b += deviation(agent, b).powi(2);
+            }
+
+            let scale: f32 = (variance_a * variance_b).sqrt();
+            if scale > 0.0 {
+                covariance / scale
+            } else {
+                0.0
+            }
+        };
+
+        let size = centroid.len();
+        let matrix = (0..size)
+            .map(|a| (0..size).map(|b| correlation(a, b)).collect())
+            .collect();
+
+        Some(matrix)
+    }
+
+    /// Produces one offspring per agent.
+    ///
+    /// Agent `i` is mutated with an RNG seeded from `seed + i`, so the result
+    /// only depends on the seed and not on how the work is split between
+    /// threads.
+    pub fn reproduce_all(&self, seed: u64) -> Vec<Agent<'a>> {
+        let reproduce = |(idx, agent): (usize, &Agent<'a>)| {
+            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(idx as u64));
+            agent.reproduce_with_rng(&self.config, &mut rng)
+        };
+
+        #[cfg(feature = "rayon")]
+        return self.agents.par_iter().enumerate().map(reproduce).collect();
+
+        #[cfg(not(feature = "rayon"))]
+        return self.agents.iter().enumerate().map(reproduce).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::{agent, connection, no_mutations},
+        TANH,
+    };
+
+    #[test]
+    fn evolve_keeps_the_champion_first() {
+        let mut agents = Agent::create_agents(4, 1, 1, vec![TANH, TANH]);
+        agents[2].add_input();
+        let mut population = Population::new(agents, MutationConfig::default());
+
+        let best = population.evolve(|agent| agent.inputs as f32);
+
+        assert_eq!(best, 2.0);
+        assert_eq!(population.agents().len(), 4);
+        assert!(population.agents().iter().all(|agent| agent.inputs == 2));
+    }
+
+    #[test]
+    fn collected_outputs_match_calculate() {
+        let mut agents = Agent::create_agents(3, 1, 1, vec![TANH, TANH]);
+        let config = MutationConfig {
+            new_connection_chance: 1.0,
+            ..MutationConfig::default()
+        };
+        for agent in agents.iter_mut() {
+            *agent = agent.reproduce_with_config(&config);
+        }
+        let mut population = Population::new(agents.clone(), config);
+
+        let collected = population.evaluate_and_collect(|agent| agent.calculate(&[0.5])[0] * 2.0);
+
+        for ((score, output), agent) in collected.iter().zip(agents.iter_mut()) {
+            assert_eq!(*output, agent.calculate(&[0.5]));
+            assert_eq!(*score, output[0] * 2.0);
+        }
+    }
+
+    #[test]
+    fn custom_selection_is_honored() {
+        struct First;
+
+        impl Selection for First {
+            fn select(&self, _: &[f32], num: usize, _: &mut impl rand::Rng) -> Vec<usize> {
+                vec![0; num]
+            }
+        }
+
+        let mut agents = Agent::create_agents(4, 1, 1, vec![TANH, TANH]);
+        for agent in agents.iter_mut().skip(1) {
+            agent.add_input();
+        }
+        let mut population = Population::new(agents, MutationConfig::default());
+
+        population.evolve_with_selection(&First, |agent| agent.inputs as f32);
+
+        let inputs: Vec<i32> = population
+            .agents()
+            .iter()
+            .map(|agent| agent.inputs)
+            .collect();
+        assert_eq!(inputs, vec![2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn generation_counts_evolve_calls() {
+        let mut population = Population::new(
+            Agent::create_agents(2, 1, 1, vec![TANH, TANH]),
+            MutationConfig::default(),
+        );
+        assert_eq!(population.generation(), 0);
+
+        for generation in 1..=3 {
+            population.evolve(|_| 0.0);
+            assert_eq!(population.generation(), generation);
+        }
+    }
+
+    #[test]
+    fn diversity_of_identical_and_mixed_populations() {
+        let mut population = Population::new(
+            Agent::create_agents(4, 2, 1, vec![TANH, TANH]),
+            MutationConfig::default(),
+        );
+        assert_eq!(population.structural_diversity(), 0.0);
+
+        population.agents_mut()[1].add_output();
+        population.agents_mut()[2].add_input();
+        population.agents_mut()[3].add_input();
+        population.agents_mut()[3].add_output();
+        assert_eq!(population.structural_diversity(), 2.0);
+    }
+
+    #[test]
+    fn split_is_reproducible() {
+        let mut agents = Agent::create_agents(10, 1, 1, vec![TANH, TANH]);
+        for (idx, agent) in agents.iter_mut().enumerate() {
+            for _ in 0..idx {
+                agent.add_input();
+            }
+        }
+        let mut population = Population::new(agents, MutationConfig::default());
+        population.set_seed(11);
+        let ids = |population: &Population| -> Vec<i32> {
+            population
+                .agents()
+                .iter()
+                .map(|agent| agent.inputs)
+                .collect()
+        };
+
+        let (train, validation) = population.split(0.7);
+        let (train_again, validation_again) = population.split(0.7);
+
+        assert_eq!(train.agents().len(), 7);
+        assert_eq!(validation.agents().len(), 3);
+        assert_eq!(ids(&train), ids(&train_again));
+        assert_eq!(ids(&validation), ids(&validation_again));
+    }
+
+    #[test]
+    fn snapshot_is_independent() {
+        let config = MutationConfig {
+            new_connection_chance: 0.9,
+            change_weight_chance: 0.9,
+            ..MutationConfig::default()
+        };
+        let mut population =
+            Population::new(Agent::create_agents(3, 2, 1, vec![TANH, TANH]), config);
+        population.set_seed(5);
+
+        let mut edited = population.snapshot();
+        edited.agents_mut().pop();
+        edited.agents_mut()[0].add_input();
+        assert_eq!(population.agents().len(), 3);
+        assert_eq!(population.agents()[0].inputs, 2);
+
+        // The snapshot gets its own copy of the RNG, so evolving it first
+        // leaves the original to draw the very same numbers afterwards.
+        let mut snapshot = population.snapshot();
+        let fitness = |agent: &mut Agent| agent.calculate(&[1.0, 1.0])[0];
+        for _ in 0..3 {
+            snapshot.evolve(fitness);
+        }
+        for _ in 0..3 {
+            population.evolve(fitness);
+        }
+
+        assert!(population
+            .agents()
+            .iter()
+            .any(|agent| !agent.connection_list.is_empty()));
+        for (original, copy) in population.agents().iter().zip(snapshot.agents()) {
+            assert_eq!(original.connection_list, copy.connection_list);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn seeded_reproduction_ignores_thread_count() {
+        let config = MutationConfig {
+            new_node_chance: 0.5,
+            new_connection_chance: 0.9,
+            ..MutationConfig::default()
+        };
+        let mut population =
+            Population::new(Agent::create_agents(8, 3, 2, vec![TANH, TANH]), config);
+        for _ in 0..5 {
+            population.agents = population.reproduce_all(7);
+        }
+
+        let run = |threads: usize| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| population.reproduce_all(42))
+        };
+        let serial = run(1);
+        let parallel = run(4);
+
+        for (a, b) in serial.iter().zip(parallel.iter()) {
+            assert_eq!(a.nodes, b.nodes);
+            assert_eq!(a.connection_list, b.connection_list);
+        }
+    }
+
+    #[test]
+    fn improvement_rate_follows_history() {
+        let agents = Agent::create_agents(3, 1, 1, vec![TANH, TANH]);
+        let mut rising = Population::new(agents.clone(), MutationConfig::default());
+        let mut flat = Population::new(agents, MutationConfig::default());
+
+        for generation in 0..5 {
+            rising.evolve(|_| generation as f32);
+            flat.evolve(|_| 1.0);
+        }
+
+        assert_eq!(rising.fitness_history(), [0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(rising.improvement_rate(3), 1.0);
+        assert_eq!(rising.improvement_rate(10), 1.0);
+        assert_eq!(flat.improvement_rate(3), 0.0);
+    }
+
+    #[test]
+    fn centroid_of_shared_topology() {
+        let weights = [[0.5, -1.0], [1.5, 2.0], [-0.5, 0.5]];
+        let agents: Vec<Agent> = weights
+            .iter()
+            .map(|&[a, b]| {
+                agent(
+                    2,
+                    0,
+                    1,
+                    vec![connection(0, 1, 2, 0, b), connection(0, 0, 2, 0, a)],
+                )
+            })
+            .collect();
+        let mut population = Population::new(agents, MutationConfig::default());
+
+        assert_eq!(population.weight_centroid(), Some(vec![0.5, 0.5]));
+
+        population.agents_mut()[1].connection_list.pop();
+        assert_eq!(population.weight_centroid(), None);
+    }
+
+    #[test]
+    fn evolve_for_respects_budget() {
+        let agents = Agent::create_agents(4, 1, 1, vec![TANH, TANH]);
+        let mut population = Population::new(agents, MutationConfig::default());
+        let start = Instant::now();
+
+        let generations = population.evolve_for(
+            |agent| agent.calculate(&[1.0])[0],
+            Duration::from_millis(20),
+        );
+
+        assert!(generations >= 1);
+        assert_eq!(population.generation(), generations);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn better_champion_wins() {
+        let strong = Population::new(
+            vec![agent(1, 0, 1, vec![connection(0, 0, 2, 0, 2.0)])],
+            MutationConfig::default(),
+        );
+        let weak = Population::new(
+            vec![agent(1, 0, 1, vec![connection(0, 0, 2, 0, -2.0)])],
+            MutationConfig::default(),
+        );
+        let fitness = |agent: &mut Agent| agent.calculate(&[1.0])[0];
+
+        assert!(strong.beats(&weak, fitness));
+        assert!(!weak.beats(&strong, fitness));
+        assert!(!strong.beats(&strong, fitness));
+
+        let hidden_champion = Population::new(
+            vec![
+                agent(1, 0, 1, vec![connection(0, 0, 2, 0, -2.0)]),
+                agent(1, 0, 1, vec![connection(0, 0, 2, 0, 3.0)]),
+            ],
+            MutationConfig::default(),
+        );
+        assert!(hidden_champion.beats(&strong, fitness));
+        assert!(!strong.beats(&hidden_champion, fitness));
+    }
+
+    #[test]
+    fn reproduction_bias_breeds_top_agents_most() {
+        let agents: Vec<Agent> = (0..9)
+            .map(|idx| agent(1, 0, 1, vec![connection(0, 0, 2, 0, idx as f32)]))
+            .collect();
+        let mut population = Population::new(agents, no_mutations());
+        population.set_reproduction_bias(Some(3.0));
+
+        population.evolve(|agent| agent.connection_list[0].weight);
+
+        let offspring = |weight: f32| {
+            population.agents()[1..]
+                .iter()
+                .filter(|agent| agent.connection_list[0].weight == weight)
+                .count()
+        };
+        assert!(offspring(8.0) > offspring(4.0));
+    }
+
+    #[test]
+    fn champion_history_keeps_every_generation() {
+        let agents = Agent::create_agents(6, 2, 1, vec![TANH, TANH]);
+        let mut population = Population::new(agents, MutationConfig::default());
+        population.set_seed(12);
+        population.record_champions(None);
+        let fitness = |agent: &mut Agent| agent.calculate(&[1.0, -1.0])[0];
+        let mut champions = vec![];
+
+        for _ in 0..5 {
+            let mut scored = population.agents().to_vec();
+            let scores: Vec<f32> = scored.iter_mut().map(fitness).collect();
+            champions.push(scored[best_index(&scores).unwrap()].genome_hash());
+            population.evolve(fitness);
+        }
+
+        let history: Vec<u64> = population
+            .champion_history()
+            .iter()
+            .map(|agent| agent.genome_hash())
+            .collect();
+        assert_eq!(history, champions);
+
+        population.record_champions(Some(2));
+        assert_eq!(population.champion_history().len(), 2);
+        assert_eq!(population.champion_history()[1].genome_hash(), champions[4]);
+    }
+
+    #[test]
+    fn weights_moving_together_correlate() {
+        let agents: Vec<Agent> = [0.1, 0.7, -0.4, 1.2]
+            .iter()
+            .zip([0.5, 0.5, -0.5, -0.5])
+            .map(|(&a, c)| {
+                agent(
+                    3,
+                    0,
+                    1,
+                    vec![
+                        connection(0, 0, 2, 0, a),
+                        connection(0, 1, 2, 0, 2.0 * a + 1.0),
+                        connection(0, 2, 2, 0, c),
+                    ],
+                )
+            })
+            .collect();
+        let mut population = Population::new(agents, MutationConfig::default());
+
+        let matrix = population.weight_correlation_matrix().unwrap();
+        assert!((matrix[0][1] - 1.0).abs() < 1e-5);
+        assert!((matrix[1][0] - 1.0).abs() < 1e-5);
+        assert!(matrix[0][2].abs() < 1e-5);
+
+        population.agents_mut()[1].connection_list.pop();
+        assert_eq!(population.weight_correlation_matrix(), None);
+    }
+}
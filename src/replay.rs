@@ -0,0 +1,73 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::Agent;
+
+/// An agent together with the RNG seed of an evaluation, so the exact episode
+/// can be run again later.
+#[derive(Clone)]
+pub struct ReplayContext<'a> {
+    agent: Agent<'a>,
+    seed: u64,
+}
+
+impl<'a> ReplayContext<'a> {
+    pub fn new(agent: &Agent<'a>, seed: u64) -> Self {
+        ReplayContext {
+            agent: agent.clone(),
+            seed,
+        }
+    }
+
+    pub fn agent(&self) -> &Agent<'a> {
+        &self.agent
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns a new RNG in the state the episode started with.
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
+    }
+
+    /// Runs `episode` on a copy of the saved agent with a freshly seeded RNG.
+    ///
+    /// As long as the episode draws all of its randomness from the given RNG,
+    /// every replay produces the same result.
+    pub fn replay<T>(&self, episode: impl FnOnce(&mut Agent<'a>, &mut StdRng) -> T) -> T {
+        let mut agent = self.agent.clone();
+
+        episode(&mut agent, &mut self.rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::tests::{agent, connection};
+
+    #[test]
+    fn replay_reproduces_episode() {
+        let champion = agent(
+            2,
+            1,
+            1,
+            vec![connection(0, 0, 1, 0, 1.5), connection(1, 0, 2, 0, -0.5)],
+        );
+        let context = ReplayContext::new(&champion, 1234);
+        let episode = |agent: &mut Agent, rng: &mut StdRng| {
+            (0..10)
+                .map(|_| {
+                    let input = vec![rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)];
+                    let output = agent.calculate(&input);
+                    (input, output)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(context.replay(episode), context.replay(episode));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::MutationConfig;
+
+/// Picks the mutation config for a generation from its best fitness.
+///
+/// Implemented for any `FnMut(f32) -> MutationConfig`.
+pub trait MutationSchedule {
+    fn config(&mut self, best_fitness: f32) -> MutationConfig;
+}
+
+impl<F: FnMut(f32) -> MutationConfig> MutationSchedule for F {
+    fn config(&mut self, best_fitness: f32) -> MutationConfig {
+        self(best_fitness)
+    }
+}
+
+/// Moves every chance, `max_weight` and `perturb_strength` linearly from
+/// `start` to `end` as the best fitness rises from `fitness_range.0` to
+/// `fitness_range.1`.
+///
+/// The remaining fields are taken from `start`.
+#[derive(Clone, Copy)]
+pub struct LinearAnneal {
+    pub start: MutationConfig,
+    pub end: MutationConfig,
+    pub fitness_range: (f32, f32),
+}
+
+impl MutationSchedule for LinearAnneal {
+    fn config(&mut self, best_fitness: f32) -> MutationConfig {
+        let (low, high) = self.fitness_range;
+        let t = if high > low {
+            ((best_fitness - low) / (high - low)).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let lerp = |start: f32, end: f32| start + (end - start) * t;
+        let (start, end) = (&self.start, &self.end);
+
+        MutationConfig {
+            new_node_chance: lerp(start.new_node_chance, end.new_node_chance),
+            new_connection_chance: lerp(start.new_connection_chance, end.new_connection_chance),
+            delete_node_chance: lerp(start.delete_node_chance, end.delete_node_chance),
+            delete_connection_chance: lerp(
+                start.delete_connection_chance,
+                end.delete_connection_chance,
+            ),
+            change_weight_chance: lerp(start.change_weight_chance, end.change_weight_chance),
+            change_connection_chance: lerp(
+                start.change_connection_chance,
+                end.change_connection_chance,
+            ),
+            max_weight: lerp(start.max_weight, end.max_weight),
+            perturb_weight_chance: lerp(start.perturb_weight_chance, end.perturb_weight_chance),
+            perturb_strength: lerp(start.perturb_strength, end.perturb_strength),
+            flip_sign_chance: lerp(start.flip_sign_chance, end.flip_sign_chance),
+            reroute_connection_chance: lerp(
+                start.reroute_connection_chance,
+                end.reroute_connection_chance,
+            ),
+            change_activation_chance: lerp(
+                start.change_activation_chance,
+                end.change_activation_chance,
+            ),
+            add_connected_node_chance: lerp(
+                start.add_connected_node_chance,
+                end.add_connected_node_chance,
+            ),
+            ..*start
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Agent, Population, TANH};
+
+    #[test]
+    fn linear_anneal_lowers_chances_as_fitness_improves() {
+        let mut anneal = LinearAnneal {
+            start: MutationConfig {
+                change_weight_chance: 0.8,
+                ..MutationConfig::default()
+            },
+            end: MutationConfig {
+                change_weight_chance: 0.0,
+                ..MutationConfig::default()
+            },
+            fitness_range: (0.0, 1.0),
+        };
+        let mut population = Population::new(
+            Agent::create_agents(3, 1, 1, vec![TANH, TANH]),
+            MutationConfig::default(),
+        );
+
+        population.evolve_with_schedule(&mut anneal, |_| 0.25);
+        let early = population.config().change_weight_chance;
+        population.evolve_with_schedule(&mut anneal, |_| 0.75);
+        let late = population.config().change_weight_chance;
+
+        assert!((early - 0.6).abs() < 1e-6);
+        assert!((late - 0.2).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{layer_order, Agent};
+
+/// Reusable value buffers for evaluating agents without allocating.
+///
+/// One `Scratch` can be shared by any number of agents, the buffers are
+/// resized to each agent's layers and only grow when needed.
+#[derive(Clone, Default)]
+pub struct Scratch {
+    data_lists: [Vec<f32>; 3],
+    /// Connection indices in the order `calculate` processes them.
+    order: Vec<usize>,
+}
+
+impl Scratch {
+    pub fn new() -> Self {
+        Scratch::default()
+    }
+
+    /// Zeroes every buffer in place.
+    pub fn reset(&mut self) {
+        for list in self.data_lists.iter_mut() {
+            list.fill(0.0);
+        }
+    }
+
+    /// The values of every layer from the last evaluation.
+    pub fn data_lists(&self) -> &[Vec<f32>] {
+        &self.data_lists
+    }
+}
+
+impl Agent<'_> {
+    /// Same as [`Agent::calculate`], but only reads the agent and writes all
+    /// values into `scratch`, returning the outputs.
+    pub fn calculate_in<'s>(&self, input: &[f32], scratch: &'s mut Scratch) -> &'s [f32] {
+        if input.len() != self.inputs.try_into().unwrap() {
+            panic!(
+                "Input size ({}) doesn't match target input size ({})",
+                input.len(),
+                self.inputs
+            );
+        }
+
+        let [inputs, hidden, outputs] = &mut scratch.data_lists;
+        inputs.clear();
+        inputs.extend(self.masked_input(input));
+        hidden.resize(self.nodes.try_into().unwrap(), 0.0);
+        outputs.resize(self.outputs.try_into().unwrap(), 0.0);
+        hidden.fill(0.0);
+        outputs.fill(0.0);
+
+        scratch.order.clear();
+        scratch.order.extend(0..self.connection_list.len());
+        scratch
+            .order
+            .sort_by(|&a, &b| layer_order(&self.connection_list[a], &self.connection_list[b]));
+
+        for &idx in &scratch.order {
+            let connection = &self.connection_list[idx];
+            let value = connection.transmit(
+                &self.activation_funcs,
+                self.activation_clamp,
+                scratch.data_lists[connection.start_layer][connection.start_idx],
+            );
+            scratch.data_lists[connection.end_layer][connection.end_idx] += value;
+        }
+
+        &scratch.data_lists[2]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{agent, connection};
+
+    #[test]
+    fn reused_scratch_matches_fresh_evaluation() {
+        let mut first = agent(
+            2,
+            1,
+            1,
+            vec![
+                connection(1, 0, 2, 0, 0.5),
+                connection(0, 0, 1, 0, 1.5),
+                connection(0, 1, 2, 0, -0.5),
+            ],
+        );
+        let mut second = agent(2, 0, 1, vec![connection(0, 1, 2, 0, 2.0)]);
+        let mut scratch = Scratch::new();
+
+        assert_eq!(
+            first.calculate_in(&[0.3, 0.9], &mut scratch),
+            first.calculate(&[0.3, 0.9])
+        );
+        scratch.reset();
+        assert_eq!(
+            second.calculate_in(&[0.3, 0.9], &mut scratch),
+            second.calculate(&[0.3, 0.9])
+        );
+        assert_eq!(
+            first.calculate_in(&[-0.2, 0.1], &mut scratch),
+            first.calculate(&[-0.2, 0.1])
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::cmp::Ordering;
+
+use rand::Rng;
+
+/// Picks parents for the next generation from the fitness of every agent.
+pub trait Selection {
+    /// Returns `num` indices into `fitnesses`, repeats allowed.
+    fn select(&self, fitnesses: &[f32], num: usize, rng: &mut impl Rng) -> Vec<usize>;
+}
+
+/// Picks the fittest of `size` randomly drawn agents, once per parent.
+#[derive(Clone, Copy, Debug)]
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl Selection for Tournament {
+    fn select(&self, fitnesses: &[f32], num: usize, rng: &mut impl Rng) -> Vec<usize> {
+        (0..num)
+            .map(|_| {
+                (0..self.size.max(1))
+                    .map(|_| rng.gen_range(0..fitnesses.len()))
+                    .max_by(|&a, &b| {
+                        fitnesses[a]
+                            .partial_cmp(&fitnesses[b])
+                            .unwrap_or(Ordering::Equal)
+                    })
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+/// Picks agents with a chance proportional to their fitness.
+///
+/// Fitnesses are shifted so that the lowest one counts as 0. If every agent
+/// has the same fitness they are picked uniformly.
+#[derive(Clone, Copy, Debug)]
+pub struct Roulette;
+
+impl Selection for Roulette {
+    fn select(&self, fitnesses: &[f32], num: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let min = fitnesses.iter().cloned().fold(f32::INFINITY, f32::min);
+        let weights: Vec<f32> = fitnesses.iter().map(|fitness| fitness - min).collect();
+        let total: f32 = weights.iter().sum();
+
+        (0..num)
+            .map(|_| {
+                if total.is_nan() || total <= 0.0 {
+                    return rng.gen_range(0..fitnesses.len());
+                }
+
+                let mut target = rng.gen_range(0.0..total);
+                for (idx, weight) in weights.iter().enumerate() {
+                    if target < *weight {
+                        return idx;
+                    }
+                    target -= weight;
+                }
+
+                weights.len() - 1
+            })
+            .collect()
+    }
+}
+
+/// Cycles through the `keep` fittest agents.
+#[derive(Clone, Copy, Debug)]
+pub struct Truncation {
+    pub keep: usize,
+}
+
+impl Selection for Truncation {
+    fn select(&self, fitnesses: &[f32], num: usize, _: &mut impl Rng) -> Vec<usize> {
+        let mut ranked: Vec<usize> = (0..fitnesses.len()).collect();
+        ranked.sort_by(|&a, &b| {
+            fitnesses[b]
+                .partial_cmp(&fitnesses[a])
+                .unwrap_or(Ordering::Equal)
+        });
+        ranked.truncate(self.keep.max(1));
+
+        ranked.iter().cycle().take(num).cloned().collect()
+    }
+}
+
+/// Hands out parents in proportion to fitness rank, deterministically.
+///
+/// The agent ranked `r` (0 being the fittest) of `n` gets a share of
+/// `(n - r)^bias`, so a bias of 0 spreads offspring evenly and higher biases
+/// concentrate them on the top agents. The result is sorted by rank.
+#[derive(Clone, Copy, Debug)]
+pub struct RankBias {
+    pub bias: f32,
+}
+
+impl Selection for RankBias {
+    fn select(&self, fitnesses: &[f32], num: usize, _: &mut impl Rng) -> Vec<usize> {
+        let mut ranked: Vec<usize> = (0..fitnesses.len()).collect();
+        ranked.sort_by(|&a, &b| {
+            fitnesses[b]
+                .partial_cmp(&fitnesses[a])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let shares: Vec<f32> = (0..ranked.len())
+            .map(|rank| ((ranked.len() - rank) as f32).powf(self.bias))
+            .collect();
+        let total: f32 = shares.iter().sum();
+        let quotas: Vec<f32> = shares
+            .iter()
+            .map(|share| share / total * num as f32)
+            .collect();
+
+        // Whole quotas first, then the largest remainders fill up the rest.
+        let mut counts: Vec<usize> = quotas.iter().map(|quota| quota.floor() as usize).collect();
+        let mut by_remainder: Vec<usize> = (0..ranked.len()).collect();
+        by_remainder.sort_by(|&a, &b| {
+            quotas[b]
+                .fract()
+                .partial_cmp(&quotas[a].fract())
+                .unwrap_or(Ordering::Equal)
+        });
+        let missing = num - counts.iter().sum::<usize>();
+        for &rank in by_remainder.iter().take(missing) {
+            counts[rank] += 1;
+        }
+
+        ranked
+            .iter()
+            .zip(counts)
+            .flat_map(|(&idx, count)| std::iter::repeat_n(idx, count))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn strategies_favor_the_fittest() {
+        let fitnesses = [0.0, 5.0, 1.0, 0.0];
+        let mut rng = thread_rng();
+
+        assert_eq!(
+            Truncation { keep: 2 }.select(&fitnesses, 3, &mut rng),
+            vec![1, 2, 1]
+        );
+        assert_eq!(
+            Tournament { size: 64 }.select(&fitnesses, 3, &mut rng),
+            vec![1, 1, 1]
+        );
+        assert!(Roulette
+            .select(&fitnesses, 20, &mut rng)
+            .iter()
+            .all(|&idx| idx == 1 || idx == 2));
+    }
+
+    #[test]
+    fn rank_bias_favors_top_ranks() {
+        let fitnesses = [2.0, 9.0, 5.0, 1.0, 7.0];
+        let mut rng = thread_rng();
+
+        let parents = RankBias { bias: 2.0 }.select(&fitnesses, 20, &mut rng);
+        let count = |idx| parents.iter().filter(|&&parent| parent == idx).count();
+
+        assert_eq!(parents.len(), 20);
+        assert!(count(1) > count(2));
+        assert!(count(2) > count(3));
+        assert_eq!(
+            RankBias { bias: 0.0 }.select(&fitnesses, 5, &mut rng),
+            vec![1, 4, 2, 0, 3]
+        );
+    }
+}
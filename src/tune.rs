@@ -0,0 +1,112 @@
+use std::ops::RangeInclusive;
+
+use crate::{Agent, MutationConfig, Population};
+
+/// A `MutationConfig` field that [`tune`] can search over.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Parameter {
+    NewNodeChance,
+    NewConnectionChance,
+    DeleteNodeChance,
+    DeleteConnectionChance,
+    ChangeWeightChance,
+    ChangeConnectionChance,
+    MaxWeight,
+}
+
+impl Parameter {
+    pub fn set(&self, config: &mut MutationConfig, value: f32) {
+        match self {
+            Parameter::NewNodeChance => config.new_node_chance = value,
+            Parameter::NewConnectionChance => config.new_connection_chance = value,
+            Parameter::DeleteNodeChance => config.delete_node_chance = value,
+            Parameter::DeleteConnectionChance => config.delete_connection_chance = value,
+            Parameter::ChangeWeightChance => config.change_weight_chance = value,
+            Parameter::ChangeConnectionChance => config.change_connection_chance = value,
+            Parameter::MaxWeight => config.max_weight = value,
+        }
+    }
+}
+
+/// Grid searches mutation parameters with short evolution runs.
+///
+/// Every parameter is tried at `steps` evenly spaced values within its range,
+/// starting from the population's current config. Each combination evolves a
+/// snapshot of `population` for `generations` generations and the config with
+/// the highest final best fitness is returned.
+pub fn tune<'a>(
+    population: &Population<'a>,
+    ranges: &[(Parameter, RangeInclusive<f32>)],
+    steps: usize,
+    generations: usize,
+    mut fitness: impl FnMut(&mut Agent<'a>) -> f32,
+) -> MutationConfig {
+    let value = |range: &RangeInclusive<f32>, step: usize| {
+        if steps <= 1 {
+            *range.start()
+        } else {
+            range.start() + (range.end() - range.start()) * step as f32 / (steps - 1) as f32
+        }
+    };
+
+    let mut best_config = *population.config();
+    let mut best_score = f32::NEG_INFINITY;
+    let mut grid = vec![0; ranges.len()];
+
+    loop {
+        let mut config = *population.config();
+        for ((parameter, range), step) in ranges.iter().zip(&grid) {
+            parameter.set(&mut config, value(range, *step));
+        }
+
+        let mut trial = population.snapshot();
+        trial.set_config(config);
+
+        let mut score = f32::NEG_INFINITY;
+        for _ in 0..generations {
+            score = trial.evolve(&mut fitness);
+        }
+
+        if score > best_score {
+            best_score = score;
+            best_config = config;
+        }
+
+        let Some(axis) = grid.iter().position(|step| step + 1 < steps.max(1)) else {
+            break;
+        };
+        grid[axis] += 1;
+        grid[..axis].fill(0);
+    }
+
+    best_config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TANH;
+
+    #[test]
+    fn tune_prefers_growing_connections() {
+        let mut population = Population::new(
+            Agent::create_agents(5, 1, 1, vec![TANH, TANH]),
+            MutationConfig::default(),
+        );
+        population.set_seed(3);
+
+        let config = tune(
+            &population,
+            &[
+                (Parameter::NewConnectionChance, 0.0..=1.0),
+                (Parameter::MaxWeight, 1.0..=2.0),
+            ],
+            2,
+            10,
+            |agent| agent.calculate(&[1.0])[0],
+        );
+
+        assert_eq!(config.new_connection_chance, 1.0);
+        assert!((1.0..=2.0).contains(&config.max_weight));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::Agent;
+
+/// Types that can be fed to an agent as its inputs.
+pub trait IntoInputs {
+    fn into_inputs(self) -> Vec<f32>;
+}
+
+/// Types that can be read from an agent's outputs.
+pub trait FromOutputs: Sized {
+    fn from_outputs(outputs: &[f32]) -> Self;
+}
+
+impl IntoInputs for Vec<f32> {
+    fn into_inputs(self) -> Vec<f32> {
+        self
+    }
+}
+
+impl<const N: usize> IntoInputs for [f32; N] {
+    fn into_inputs(self) -> Vec<f32> {
+        self.to_vec()
+    }
+}
+
+impl FromOutputs for Vec<f32> {
+    fn from_outputs(outputs: &[f32]) -> Self {
+        outputs.to_vec()
+    }
+}
+
+impl Agent<'_> {
+    /// Same as [`Agent::calculate`], but converts the input from and the
+    /// output to user types.
+    pub fn calculate_typed<O: FromOutputs>(&mut self, input: impl IntoInputs) -> O {
+        O::from_outputs(&self.calculate(&input.into_inputs()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{agent, connection};
+
+    struct Observation {
+        position: f32,
+        velocity: f32,
+    }
+
+    impl IntoInputs for Observation {
+        fn into_inputs(self) -> Vec<f32> {
+            vec![self.position, self.velocity]
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Action {
+        throttle: f32,
+        steering: f32,
+    }
+
+    impl FromOutputs for Action {
+        fn from_outputs(outputs: &[f32]) -> Self {
+            Action {
+                throttle: outputs[0],
+                steering: outputs[1],
+            }
+        }
+    }
+
+    #[test]
+    fn structs_round_trip_through_agent() {
+        let mut agent = agent(
+            2,
+            0,
+            2,
+            vec![connection(0, 0, 2, 0, 1.0), connection(0, 1, 2, 1, -2.0)],
+        );
+        let observation = Observation {
+            position: 0.5,
+            velocity: -0.25,
+        };
+        let expected = agent.calculate(&[0.5, -0.25]);
+
+        let action: Action = agent.calculate_typed(observation);
+
+        assert_eq!(
+            action,
+            Action {
+                throttle: expected[0],
+                steering: expected[1],
+            }
+        );
+    }
+}